@@ -0,0 +1,46 @@
+use core::num::NonZero;
+
+use acpi::HpetInfo;
+
+use crate::*;
+
+/// Maps the HPET's physical memory into virtual memory, similar to `acpi::Handler`.
+///
+/// # Safety
+/// The returned virtual address must be mapped to the given physical address as un-cacheable (UC), for at least `size` bytes, and must stay mapped for as long as the [`Hpet`] is used.
+pub unsafe trait HpetMapper {
+    /// # Safety
+    /// `phys` must be the physical address of the HPET
+    unsafe fn map(&mut self, phys: u64, size: usize) -> NonZero<usize>;
+}
+
+impl Hpet<'_> {
+    /// The physical address that needs to be mapped, from the `acpi` crate's [`HpetInfo`]
+    pub fn base_address(info: &HpetInfo) -> u64 {
+        info.base_address as u64
+    }
+
+    /// Same as [`Hpet::new`], but checks (in debug builds) that the HPET matches the `acpi` crate's [`HpetInfo`].
+    /// Map [`Hpet::base_address`] using [`HPET_MMIO_SIZE`] to get `addr`.
+    ///
+    /// # Safety
+    /// The address must be a virtual address mapped to HPET memory as un-cacheable (UC).
+    pub unsafe fn new_with_info(info: &HpetInfo, addr: NonZero<usize>) -> Self {
+        let hpet = unsafe { Self::new(addr) };
+        debug_assert_eq!(
+            hpet.vendor_id(),
+            info.pci_vendor_id,
+            "The HPET's vendor ID does not match the ACPI table"
+        );
+        hpet
+    }
+
+    /// Maps the HPET described by the `acpi` crate's [`HpetInfo`] using `mapper`, and returns an [`Hpet`]
+    ///
+    /// # Safety
+    /// `info` must describe the real HPET, for example by getting it from the ACPI tables with `acpi::HpetInfo::new`.
+    pub unsafe fn from_acpi<M: HpetMapper>(info: &HpetInfo, mapper: &mut M) -> Self {
+        let addr = unsafe { mapper.map(Self::base_address(info), HPET_MMIO_SIZE) };
+        unsafe { Self::new_with_info(info, addr) }
+    }
+}
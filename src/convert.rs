@@ -0,0 +1,98 @@
+use core::time::Duration;
+
+/// Converts a [`Duration`] into ticks, rounded to the nearest tick, saturating at `u64::MAX`.
+/// A period of 0 (which is not allowed by the spec) also saturates, instead of dividing by 0.
+pub fn ticks_for_duration(period_fs: u32, duration: Duration) -> u64 {
+    ticks_for_duration_u128(period_fs, duration).min(u64::MAX as u128) as u64
+}
+
+pub(crate) fn ticks_for_duration_u128(period_fs: u32, duration: Duration) -> u128 {
+    let period_fs = period_fs as u128;
+    let femtoseconds = duration.as_nanos() * 1_000_000;
+    (femtoseconds + period_fs / 2)
+        .checked_div(period_fs)
+        .unwrap_or(u128::MAX)
+}
+
+/// Converts ticks into a [`Duration`].
+/// The HPET has femtosecond resolution, but [`Duration`] only has nanosecond resolution, so the result is truncated to the nanosecond.
+pub fn duration_for_ticks(period_fs: u32, ticks: u64) -> Duration {
+    let femtoseconds = ticks as u128 * period_fs as u128;
+    let nanoseconds = femtoseconds / 1_000_000;
+    Duration::new(
+        (nanoseconds / 1_000_000_000) as u64,
+        (nanoseconds % 1_000_000_000) as u32,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MAX_TICK_PERIOD_FS;
+
+    /// A common period of ~14.318 MHz
+    const PERIOD_FS: u32 = 69_841_279;
+
+    #[test]
+    fn ticks_round_to_nearest() {
+        assert_eq!(ticks_for_duration(PERIOD_FS, Duration::ZERO), 0);
+        assert_eq!(ticks_for_duration(PERIOD_FS, Duration::from_micros(1)), 14);
+        assert_eq!(ticks_for_duration(PERIOD_FS, Duration::from_nanos(35)), 1);
+        assert_eq!(ticks_for_duration(PERIOD_FS, Duration::from_nanos(34)), 0);
+        // Exactly half a tick rounds up
+        assert_eq!(ticks_for_duration(2_000_000, Duration::from_nanos(1)), 1);
+    }
+
+    #[test]
+    fn duration_truncates_to_nanos() {
+        assert_eq!(duration_for_ticks(PERIOD_FS, 0), Duration::ZERO);
+        assert_eq!(duration_for_ticks(PERIOD_FS, 1), Duration::from_nanos(69));
+        assert_eq!(duration_for_ticks(PERIOD_FS, 14), Duration::from_nanos(977));
+        assert_eq!(duration_for_ticks(1, 999_999), Duration::ZERO);
+        assert_eq!(duration_for_ticks(1, 1_000_000), Duration::from_nanos(1));
+    }
+
+    #[test]
+    fn max_tick_period() {
+        assert_eq!(
+            duration_for_ticks(MAX_TICK_PERIOD_FS, 1),
+            Duration::from_nanos(100)
+        );
+        assert_eq!(
+            ticks_for_duration(MAX_TICK_PERIOD_FS, Duration::from_nanos(100)),
+            1
+        );
+        assert_eq!(
+            ticks_for_duration(MAX_TICK_PERIOD_FS, Duration::from_nanos(149)),
+            1
+        );
+        assert_eq!(
+            ticks_for_duration(MAX_TICK_PERIOD_FS, Duration::from_nanos(150)),
+            2
+        );
+        assert_eq!(
+            duration_for_ticks(MAX_TICK_PERIOD_FS, u64::MAX).as_nanos(),
+            u64::MAX as u128 * 100
+        );
+    }
+
+    #[test]
+    fn ticks_saturate() {
+        assert_eq!(ticks_for_duration(1, Duration::MAX), u64::MAX);
+        assert_eq!(
+            ticks_for_duration(1, Duration::from_secs(18_446_744)),
+            u64::MAX
+        );
+        assert_eq!(
+            ticks_for_duration_u128(1, Duration::from_secs(1)),
+            1_000_000_000_000_000
+        );
+    }
+
+    #[test]
+    fn zero_period() {
+        assert_eq!(ticks_for_duration(0, Duration::from_secs(1)), u64::MAX);
+        assert_eq!(ticks_for_duration_u128(0, Duration::ZERO), u128::MAX);
+        assert_eq!(duration_for_ticks(0, u64::MAX), Duration::ZERO);
+    }
+}
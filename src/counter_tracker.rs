@@ -0,0 +1,39 @@
+use crate::*;
+
+/// Extends the main counter to 64 bits, by counting how many times it wrapped around.
+/// This is useful for 32-bit HPETs, which wrap around about every 5 minutes at 14.318 MHz.
+///
+/// **Note**: [`CounterTracker::update`] must be called at least once every wrap period (see [`Hpet::counter_value_to_duration`] with `u32::MAX`), or else wrap-arounds will be missed.
+/// It also assumes that the main counter is not written to with [`Hpet::set_main_counter_value`] while tracking.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CounterTracker {
+    last_value: u64,
+    wraps: u64,
+}
+
+impl CounterTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of times that the main counter was detected to wrap around
+    pub fn wraps(&self) -> u64 {
+        self.wraps
+    }
+
+    /// Reads the main counter and returns a monotonic 64-bit value.
+    /// If the main counter is 64-bit, this is just the main counter value.
+    pub fn update(&mut self, hpet: &Hpet) -> u64 {
+        let mask = hpet.main_counter_mask();
+        let value = hpet.main_counter_value() & mask;
+        if value < self.last_value {
+            self.wraps += 1;
+        }
+        self.last_value = value;
+        if mask == u64::MAX {
+            value
+        } else {
+            (self.wraps << mask.count_ones()) | value
+        }
+    }
+}
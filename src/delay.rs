@@ -0,0 +1,14 @@
+use core::time::Duration;
+
+use embedded_hal::delay::DelayNs;
+
+use crate::*;
+
+/// Implements `embedded_hal`'s [`DelayNs`] by busy-waiting with [`Hpet::spin_delay`]. The HPET must be enabled.
+pub struct HpetDelay<'a>(pub &'a Hpet<'a>);
+
+impl DelayNs for HpetDelay<'_> {
+    fn delay_ns(&mut self, ns: u32) {
+        self.0.spin_delay(Duration::from_nanos(ns as u64));
+    }
+}
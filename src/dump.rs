@@ -0,0 +1,53 @@
+use core::mem::offset_of;
+
+use crate::*;
+
+impl Hpet<'_> {
+    /// Copies the registers into `out`, for example to attach them to a crash report and decode them later.
+    /// The registers are written at the same offsets that they have in the HPET's memory (see [`HPET_CAPABILITIES_OFFSET`] etc.), in little endian, with reserved space filled with zeros.
+    /// Only the timers that exist are read, so at most [`Hpet::required_mmio_size`] bytes are written.
+    /// If `out` is too small, only the registers that fit are written.
+    ///
+    /// Returns the number of bytes of `out` that contain the dump.
+    pub fn dump_registers(&self, out: &mut [u8]) -> usize {
+        let len = out.len().min(self.required_mmio_size());
+        let out = &mut out[..len];
+        out.fill(0);
+        let registers = [
+            (HPET_CAPABILITIES_OFFSET, self.raw_capabilities()),
+            (HPET_CONFIG_OFFSET, self.regs().config().read().0),
+            (HPET_INTERRUPT_STATUS_OFFSET, self.raw_interrupt_status()),
+            (HPET_MAIN_COUNTER_OFFSET, self.main_counter_value()),
+        ]
+        .into_iter()
+        .chain((0..self.timers_count()).flat_map(|index| {
+            let timer_offset = HPET_TIMER0_OFFSET + index as usize * HPET_TIMER_STRIDE;
+            let timer = self.timer_regs(index);
+            [
+                (
+                    timer_offset
+                        + offset_of!(HpetTimerMemory, configuration_and_capability_register),
+                    timer.configuration_and_capability_register().read().0,
+                ),
+                (
+                    timer_offset + offset_of!(HpetTimerMemory, comparator_register),
+                    timer.comparator_register().read(),
+                ),
+                (
+                    timer_offset + offset_of!(HpetTimerMemory, fsb_interrupt_route_register),
+                    timer.fsb_interrupt_route_register().read().0,
+                ),
+            ]
+        }));
+        let mut written = 0;
+        // Stop at the first register that doesn't fit, so that timers that don't fit aren't read
+        for (offset, value) in registers {
+            let Some(bytes) = out.get_mut(offset..offset + size_of::<u64>()) else {
+                return written;
+            };
+            bytes.copy_from_slice(&value.to_le_bytes());
+            written = offset + size_of::<u64>();
+        }
+        len
+    }
+}
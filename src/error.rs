@@ -0,0 +1,76 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HpetError {
+    /// The `REV_ID` field read back as `00h`, which is not allowed by the spec. This usually means that the address is not mapped to the HPET, or is not mapped as un-cacheable (UC).
+    InvalidRevision,
+    /// The tick period (in femtoseconds) is 0 or more than [`crate::MAX_TICK_PERIOD_FS`], which is not allowed by the spec
+    InvalidTickPeriod(u32),
+    /// The main counter is 32-bit, but a timer says that it is 64-bit
+    InconsistentCounterWidth,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArmError {
+    /// The requested duration is more ticks than the timer's comparator can represent
+    DurationTooLong,
+    /// The main counter already passed the deadline when the comparator was written
+    DeadlineInPast,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InterruptConfigError {
+    /// The timer cannot send interrupts to this I/O APIC IRQ
+    UnsupportedIrq(u8),
+    /// The timer does not support FSB interrupts
+    FsbUnsupported,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimerConfigError {
+    Interrupt(InterruptConfigError),
+    /// The timer does not support periodic mode
+    PeriodicUnsupported,
+    /// The comparator value or period (the value is included) has bits set above the timer's effective width
+    ComparatorTooWide(u64),
+}
+
+impl From<InterruptConfigError> for TimerConfigError {
+    fn from(error: InterruptConfigError) -> Self {
+        Self::Interrupt(error)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LegacyConfigError {
+    /// The HPET does not support LegacyReplacement Route
+    Unsupported,
+    /// LegacyReplacement Route is not enabled. Enable it with [`crate::Hpet::set_legacy_replacement`] first.
+    NotEnabled,
+    /// The timer (the index is included) does not exist
+    MissingTimer(u8),
+    /// The timer's config (the index is included) cannot be used with the timer
+    Timer(u8, TimerConfigError),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeriodError {
+    /// The timer does not support periodic mode
+    Unsupported,
+    /// The timer is 32-bit (or forced into 32-bit mode), and the period is more than `u32::MAX` ticks
+    TooLargeFor32Bit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LegacyPitError {
+    /// The HPET does not support LegacyReplacement Route
+    Unsupported,
+    /// Timer 0 does not support periodic mode
+    PeriodicUnsupported,
+    /// The frequency (the value is included) rounds to a period of 0 ticks, or to more ticks than timer 0's comparator can hold
+    InvalidFrequency(u32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CounterWriteError {
+    /// The HPET is enabled, so the main counter cannot be written to. Halt it first with [`crate::Hpet::set_enable`].
+    CounterRunning,
+}
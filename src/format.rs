@@ -0,0 +1,87 @@
+use defmt::{Format, Formatter, write};
+
+use crate::*;
+
+impl Format for Hpet<'_> {
+    fn format(&self, f: Formatter) {
+        write!(
+            f,
+            "HPET {{ Supports 64-bit: {}, Tick Period (10^-15 s): {}, Counter Value: {}, Enabled: {}, Timers: [",
+            self.supports_64_bit_mode(),
+            self.main_counter_tick_period(),
+            self.main_counter_value(),
+            self.get_enable(),
+        );
+        for (index, timer) in self.timers().enumerate() {
+            if index > 0 {
+                write!(f, ", ");
+            }
+            write!(f, "{}", timer);
+        }
+        write!(f, "] }}");
+    }
+}
+
+impl Format for HpetTimer<'_> {
+    fn format(&self, f: Formatter) {
+        write!(
+            f,
+            "HPET Timer {{ Supports 64-bit mode: {}, Supports FSB Interrupts: {}, Supports Periodic Mode: {}, Supported I/O APIC Interrupts: {=u32:b}, Interrupt Enabled: {}, Periodic: {}, Trigger: {}, Route: ",
+            self.supports_64_bit_mode(),
+            self.supports_fsb_interrupts(),
+            self.supports_periodic_mode(),
+            self.supported_io_apic_interrupts(),
+            self.interrupt_enabled(),
+            self.is_periodic(),
+            self.interrupt_trigger(),
+        );
+        match self.fsb_route() {
+            Some((address, data)) => write!(f, "FSB {=u32:#x} <- {=u32:#x}", address, data),
+            None => write!(f, "I/O APIC IRQ {}", self.io_apic_irq().unwrap_or_default()),
+        }
+        write!(f, ", Comparator Value: {} }}", self.comparator_value());
+    }
+}
+
+impl Format for InterruptMode {
+    fn format(&self, f: Formatter) {
+        match self {
+            Self::IoApic => write!(f, "IoApic"),
+            Self::Fsb => write!(f, "Fsb"),
+        }
+    }
+}
+
+impl Format for InterruptTrigger {
+    fn format(&self, f: Formatter) {
+        match self {
+            Self::Edge => write!(f, "Edge"),
+            Self::Level => write!(f, "Level"),
+        }
+    }
+}
+
+impl Format for HpetCapabilities {
+    fn format(&self, f: Formatter) {
+        write!(
+            f,
+            "HpetCapabilities {{ vendor_id: {=u16:#x}, num_timers: {}, tick_period_fs: {}, legacy_replacement_capable: {}, supports_64_bit: {}, revision_id: {} }}",
+            self.vendor_id,
+            self.num_timers,
+            self.tick_period_fs,
+            self.legacy_replacement_capable,
+            self.supports_64_bit,
+            self.revision_id,
+        );
+    }
+}
+
+impl Format for HpetConfig {
+    fn format(&self, f: Formatter) {
+        write!(
+            f,
+            "HpetConfig {{ enabled: {}, legacy_replacement: {} }}",
+            self.enabled, self.legacy_replacement,
+        );
+    }
+}
@@ -1,11 +1,71 @@
-use core::{fmt::Debug, num::NonZero, ptr::NonNull};
+use core::{
+    fmt::{Debug, Display},
+    num::NonZero,
+    ops::Deref,
+    ptr::NonNull,
+    sync::atomic::{Ordering, compiler_fence},
+    time::Duration,
+};
 
 use volatile::{VolatilePtr, VolatileRef, access::ReadOnly};
 
 use crate::*;
 
 pub struct Hpet<'a> {
-    mmio: VolatileRef<'a, HpetMemory>,
+    pub(crate) mmio: VolatileRef<'a, HpetMemory>,
+    /// The result of [`Hpet::probe_counter_width`]
+    probed_counter_width: Option<u8>,
+    /// Set by [`Hpet::new_with_fences`]
+    fenced: bool,
+    /// The period of each timer, recorded by [`HpetTimerMut::set_periodic`], since the HPET does not let you read it back. 0 if it isn't known.
+    pub(crate) periods_ticks: [u64; HPET_MAX_TIMERS],
+}
+
+impl<'a> Hpet<'a> {
+    /// Use this if you already have a [`VolatileRef`] to the HPET's memory, for example from your own MMIO abstraction.
+    /// Just like with [`Hpet::new`], the memory must be mapped as un-cacheable (UC).
+    pub fn from_volatile_ref(mmio: VolatileRef<'a, HpetMemory>) -> Self {
+        Self {
+            mmio,
+            probed_counter_width: None,
+            fenced: false,
+            periods_ticks: [0; HPET_MAX_TIMERS],
+        }
+    }
+
+    /// The HPET's memory, with fences around the access if the HPET was created with [`Hpet::new_with_fences`]
+    pub(crate) fn regs(&self) -> Fenced<VolatilePtr<'_, HpetMemory, ReadOnly>> {
+        Fenced::new(self.mmio.as_ptr(), self.fenced)
+    }
+
+    /// Same as `regs`, but for writing
+    pub(crate) fn regs_mut(&mut self) -> Fenced<VolatilePtr<'_, HpetMemory>> {
+        Fenced::new(self.mmio.as_mut_ptr(), self.fenced)
+    }
+
+    /// A timer's registers, with fences around the access if the HPET was created with [`Hpet::new_with_fences`]
+    pub(crate) fn timer_regs(
+        &self,
+        index: u8,
+    ) -> Fenced<VolatilePtr<'_, HpetTimerMemory, ReadOnly>> {
+        Fenced::new(
+            self.mmio.as_ptr().timers().as_slice().index(index as usize),
+            self.fenced,
+        )
+    }
+
+    /// Same as `timer_regs`, but for writing
+    pub(crate) fn timer_regs_mut(&mut self, index: u8) -> Fenced<VolatilePtr<'_, HpetTimerMemory>> {
+        let fenced = self.fenced;
+        Fenced::new(
+            self.mmio
+                .as_mut_ptr()
+                .timers()
+                .as_slice()
+                .index(index as usize),
+            fenced,
+        )
+    }
 }
 
 impl Hpet<'_> {
@@ -15,96 +75,581 @@ impl Hpet<'_> {
     /// - Find the physical base address of the HPET
     /// - Map the HPET, using [`HPET_MMIO_SIZE`]
     ///
+    /// With the `acpi` feature enabled, you can use `Hpet::base_address` and `Hpet::new_with_info`, or `Hpet::from_acpi` to do the mapping with a `HpetMapper`.
+    ///
     /// # Safety
     /// The address must be a virtual address mapped to HPET memory as un-cacheable (UC).
     pub unsafe fn new(addr: NonZero<usize>) -> Self {
-        Self {
-            mmio: {
-                let pointer = NonNull::new(addr.get() as *mut HpetMemory).expect("ptr is not null");
-                unsafe { VolatileRef::new(pointer) }
-            },
+        Self::from_volatile_ref({
+            let pointer = NonNull::new(addr.get() as *mut HpetMemory).expect("ptr is not null");
+            unsafe { VolatileRef::new(pointer) }
+        })
+    }
+
+    /// Same as [`Hpet::new`], but with a memory fence before and after every access to the HPET's memory.
+    /// This is a best-effort fallback for when the HPET can only be mapped as write-combining (WC) or write-through (WT), which allow accesses to be reordered or combined.
+    /// Mapping the HPET as un-cacheable (UC) is still preferred, and is faster.
+    ///
+    /// # Safety
+    /// The address must be a virtual address mapped to HPET memory.
+    pub unsafe fn new_with_fences(addr: NonZero<usize>) -> Self {
+        let mut hpet = unsafe { Self::new(addr) };
+        hpet.fenced = true;
+        hpet
+    }
+
+    /// Same as [`Hpet::new`], but checks that the capabilities are valid before returning the [`Hpet`]:
+    /// - The revision ID is not 0
+    /// - The tick period is not 0, and is at most 100 ns
+    /// - If the main counter is 32-bit, no timer claims to be 64-bit
+    ///
+    /// # Safety
+    /// The address must be a virtual address mapped to HPET memory as un-cacheable (UC).
+    pub unsafe fn try_new(addr: NonZero<usize>) -> Result<Self, HpetError> {
+        let hpet = unsafe { Self::new(addr) };
+        let capabilities = hpet.capabilities();
+        if capabilities.revision_id == 0 {
+            return Err(HpetError::InvalidRevision);
+        }
+        if capabilities.tick_period_fs == 0 || capabilities.tick_period_fs > MAX_TICK_PERIOD_FS {
+            return Err(HpetError::InvalidTickPeriod(capabilities.tick_period_fs));
+        }
+        if !capabilities.supports_64_bit && hpet.timers().any(|timer| timer.supports_64_bit_mode())
+        {
+            return Err(HpetError::InconsistentCounterWidth);
+        }
+        Ok(hpet)
+    }
+
+    /// Gets back the address that was passed to [`Hpet::new`], so that it can be passed to [`Hpet::new`] again later.
+    /// Keeping the address mapped as un-cacheable (UC) is still your responsibility.
+    pub fn into_addr(self) -> NonZero<usize> {
+        self.mmio.into_ptr().as_raw_ptr().addr()
+    }
+
+    /// The number of bytes that need to be mapped to access the general registers and the timers that exist.
+    /// This can be less than [`HPET_MMIO_SIZE`], which has space for [`HPET_MAX_TIMERS`] timers.
+    ///
+    /// **Note**: to read the number of timers, at least the general registers need to be mapped.
+    pub fn required_mmio_size(&self) -> usize {
+        HPET_TIMER0_OFFSET + self.timers_count() as usize * HPET_TIMER_STRIDE
+    }
+
+    /// Reads all of the capabilities at once
+    pub fn capabilities(&self) -> HpetCapabilities {
+        let reg = self.regs().capabilities_and_id().read();
+        HpetCapabilities {
+            vendor_id: reg.get_vendor_id(),
+            num_timers: reg.get_num_tim_cap() + 1,
+            tick_period_fs: reg.get_counter_clk_period(),
+            legacy_replacement_capable: reg.get_leg_rt_cap(),
+            supports_64_bit: reg.get_count_size_cap(),
+            revision_id: reg.get_rev_id(),
         }
     }
 
+    /// Decodes every field of the General Capabilities and ID Register, using the names from the spec.
+    /// The register is read once, when this function is called. For example:
+    /// `VENDOR_ID=0x8086, COUNTER_CLK_PERIOD=69841279 fs, REV_ID=1, NUM_TIM_CAP=2 (3 timers), COUNT_SIZE_CAP=1 (64-bit), LEG_RT_CAP=1`
+    pub fn describe_capabilities(&self) -> impl Display {
+        let reg = self.regs().capabilities_and_id().read();
+        core::fmt::from_fn(move |f| {
+            write!(
+                f,
+                "VENDOR_ID={:#06x}, COUNTER_CLK_PERIOD={} fs, REV_ID={}, NUM_TIM_CAP={} ({} timers), COUNT_SIZE_CAP={} ({}-bit), LEG_RT_CAP={}",
+                reg.get_vendor_id(),
+                reg.get_counter_clk_period(),
+                reg.get_rev_id(),
+                reg.get_num_tim_cap(),
+                reg.get_num_tim_cap() as u16 + 1,
+                reg.get_count_size_cap() as u8,
+                if reg.get_count_size_cap() { 64 } else { 32 },
+                reg.get_leg_rt_cap() as u8,
+            )
+        })
+    }
+
+    /// The undecoded General Capabilities and ID Register, for example to identify a specific HPET
+    pub fn raw_capabilities(&self) -> u64 {
+        self.regs().capabilities_and_id().read().0
+    }
+
     pub fn vendor_id(&self) -> u16 {
-        self.mmio
-            .as_ptr()
-            .capabilities_and_id()
-            .read()
-            .get_vendor_id()
+        self.regs().capabilities_and_id().read().get_vendor_id()
+    }
+
+    /// The raw `NUM_TIM_CAP` field, which is the index of the last timer (one less than [`Hpet::timers_count`])
+    pub fn last_timer_index(&self) -> u8 {
+        self.regs().capabilities_and_id().read().get_num_tim_cap()
     }
 
     pub fn timers_count(&self) -> u8 {
-        self.mmio
-            .as_ptr()
-            .capabilities_and_id()
-            .read()
-            .get_num_tim_cap()
-            + 1
+        self.regs().capabilities_and_id().read().get_num_tim_cap() + 1
     }
 
     /// Get the main counter tick period in femtoseconds
     pub fn main_counter_tick_period(&self) -> u32 {
-        self.mmio
-            .as_ptr()
+        self.regs()
             .capabilities_and_id()
             .read()
             .get_counter_clk_period()
     }
 
+    /// Get the main counter tick period in nanoseconds
+    pub fn tick_period_ns(&self) -> f64 {
+        self.main_counter_tick_period() as f64 / 1_000_000.0
+    }
+
+    /// Get the main counter frequency in Hz (ticks per second), rounded down
+    pub fn frequency_hz(&self) -> u64 {
+        let period = self.main_counter_tick_period();
+        if period == 0 {
+            panic!("The HPET reported a tick period of 0, which is not permitted by the spec");
+        }
+        1_000_000_000_000_000 / period as u64
+    }
+
+    /// Get the main counter frequency in MHz
+    pub fn frequency_mhz(&self) -> f64 {
+        1_000_000_000.0 / self.main_counter_tick_period() as f64
+    }
+
+    /// Checks if the main counter runs at the usual 14.31818 MHz, within 500 ppm (about ±7.2 kHz).
+    /// Emulated HPETs often use other frequencies, such as 100 MHz.
+    pub fn is_standard_frequency(&self) -> bool {
+        const STANDARD_FREQUENCY_MHZ: f64 = 14.31818;
+        const TOLERANCE_PPM: f64 = 500.0;
+        (self.frequency_mhz() - STANDARD_FREQUENCY_MHZ).abs()
+            <= STANDARD_FREQUENCY_MHZ * TOLERANCE_PPM / 1_000_000.0
+    }
+
     pub fn legacy_replacement_capable(&self) -> bool {
-        self.mmio
-            .as_ptr()
-            .capabilities_and_id()
-            .read()
-            .get_leg_rt_cap()
+        self.regs().capabilities_and_id().read().get_leg_rt_cap()
     }
 
     pub fn supports_64_bit_mode(&self) -> bool {
-        self.mmio
-            .as_ptr()
+        self.regs()
             .capabilities_and_id()
             .read()
             .get_count_size_cap()
     }
 
+    /// The width of the main counter in bits, either 32 or 64.
+    /// If [`Hpet::probe_counter_width`] was called, this is the probed width instead of what the capabilities say.
+    pub fn effective_counter_width(&self) -> u8 {
+        match self.probed_counter_width {
+            Some(width) => width,
+            None => {
+                if self.supports_64_bit_mode() {
+                    64
+                } else {
+                    32
+                }
+            }
+        }
+    }
+
+    /// Finds out the actual width of the main counter (32 or 64), by writing a value with the upper 32 bits set and reading it back.
+    /// Some HPETs say that they are 64-bit even though they actually behave as 32-bit.
+    /// The result is cached, and used by [`Hpet::effective_counter_width`] from then on.
+    ///
+    /// **Note**: the first call halts the main counter while probing (see [`Hpet::with_counter_halted`]), so it falls behind real time while it is halted.
+    pub fn probe_counter_width(&mut self) -> u8 {
+        if let Some(width) = self.probed_counter_width {
+            return width;
+        }
+        let width = self.with_counter_halted(|hpet| {
+            let main_counter_value = hpet.main_counter_value();
+            hpet.set_main_counter_value(u64::MAX << 32);
+            let width = if hpet.main_counter_value() >> 32 != 0 {
+                64
+            } else {
+                32
+            };
+            hpet.set_main_counter_value(main_counter_value);
+            width
+        });
+        self.probed_counter_width = Some(width);
+        width
+    }
+
     pub fn revision_id(&self) -> u8 {
-        self.mmio.as_ptr().capabilities_and_id().read().get_rev_id()
+        self.regs().capabilities_and_id().read().get_rev_id()
+    }
+
+    /// Reads the General Configuration Register once
+    pub fn config_snapshot(&self) -> HpetConfig {
+        let reg = self.regs().config().read();
+        HpetConfig {
+            enabled: reg.get_enable_cnf(),
+            legacy_replacement: reg.get_legacy_replacement_cnf(),
+        }
     }
 
     pub fn get_enable(&self) -> bool {
-        self.mmio.as_ptr().config().read().get_enable_cnf()
+        self.regs().config().read().get_enable_cnf()
+    }
+
+    /// Checks if the main counter is actually advancing, by reading it a few times in a row.
+    /// Some buggy firmware leaves the enable bit set but the counter stuck, so this is more reliable than [`Hpet::get_enable`].
+    ///
+    /// **Note**: this can return `false` even if the counter is running, if the counter is extremely slow.
+    pub fn is_counter_running(&self) -> bool {
+        let start = self.main_counter_value();
+        (0..1000).any(|_| {
+            core::hint::spin_loop();
+            self.main_counter_value() != start
+        })
     }
 
     pub fn set_enable(&mut self, enable: bool) {
-        self.mmio.as_mut_ptr().config().update(|mut reg| {
+        self.regs_mut().config().update(|mut reg| {
             reg.set_enable_cnf(enable);
             reg
         });
     }
 
+    /// Halts the main counter, runs `f`, and then re-enables the HPET if it was enabled before.
+    /// Use this for things that need to be done while the main counter is halted, such as [`Hpet::set_main_counter_value`].
+    ///
+    /// **Note**: no timer interrupts will happen while the main counter is halted.
+    pub fn with_counter_halted<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        let enabled = self.get_enable();
+        self.set_enable(false);
+        let output = f(self);
+        self.set_enable(enabled);
+        output
+    }
+
+    /// Read, modify, and write the General Configuration Register with a single write, for example to set the enable and LegacyReplacement Route bits at the same time.
+    ///
+    /// **Note**: LegacyReplacement Route is not supported by all HPETs. Check [`Hpet::legacy_replacement_capable`] first.
+    pub fn update_config(
+        &mut self,
+        f: impl FnOnce(HpetGeneralConfigurationRegister) -> HpetGeneralConfigurationRegister,
+    ) {
+        self.regs_mut().config().update(f);
+    }
+
     /// Note that if the HPET doesn't support 64-bit mode, then the maximum value returned by this function will be `u32::MAX`.
     pub fn main_counter_value(&self) -> u64 {
-        self.mmio.as_ptr().main_counter_value_register().read()
+        self.regs().main_counter_value_register().read()
+    }
+
+    /// Same as [`Hpet::main_counter_value`], but makes sure that the read isn't reordered with other instructions, which is useful for latency measurements.
+    ///
+    /// - The compiler will not move memory accesses across the read (using [`compiler_fence`])
+    /// - On x86, `lfence` is executed before and after the read, so the read starts only after all previous instructions completed, and later instructions start only after the read completed
+    /// - On other architectures, a full memory [`fence`](core::sync::atomic::fence) is used instead of `lfence`
+    pub fn main_counter_value_fenced(&self) -> u64 {
+        serializing_fence();
+        let main_counter_value = self.main_counter_value();
+        serializing_fence();
+        main_counter_value
+    }
+
+    /// Reads the main counter using two 32-bit reads instead of one 64-bit read, which is useful if you can't do atomic 64-bit reads.
+    /// If the low 32 bits wrap between the reads, the read is retried, so the returned value is never torn.
+    pub fn main_counter_value_32_safe(&self) -> u64 {
+        if self.effective_counter_width() != 64 {
+            return self.main_counter_value_low() as u64;
+        }
+        loop {
+            let low = self.main_counter_value_low();
+            let high = self.main_counter_value_high();
+            if self.main_counter_value_low() >= low {
+                break ((high as u64) << 32) | low as u64;
+            }
+        }
+    }
+
+    fn main_counter_value_low(&self) -> u32 {
+        // The guard has to live until after the read, so that the fence after the read happens
+        let regs = self.regs();
+        // Safety: the low 32 bits are at the start of the register (little endian)
+        unsafe {
+            regs.main_counter_value_register()
+                .map(|ptr| ptr.cast::<u32>())
+        }
+        .read()
+    }
+
+    fn main_counter_value_high(&self) -> u32 {
+        let regs = self.regs();
+        // Safety: the high 32 bits are right after the low 32 bits (little endian)
+        unsafe {
+            regs.main_counter_value_register()
+                .map(|ptr| ptr.cast::<u32>().add(1))
+        }
+        .read()
     }
 
     /// **Note**: you are not allowed to write to the main counter register while the HPET is enabled.
+    /// See [`Hpet::try_set_main_counter_value`] for a non-panicking version.
     pub fn set_main_counter_value(&mut self, main_counter_value: u64) {
+        self.try_set_main_counter_value(main_counter_value)
+            .unwrap_or_else(|_| {
+                panic!("Tried to set the main counter value while the HPET was enabled")
+            });
+    }
+
+    /// Same as [`Hpet::set_main_counter_value`], but returns an error instead of panicking if the HPET is enabled
+    pub fn try_set_main_counter_value(
+        &mut self,
+        main_counter_value: u64,
+    ) -> Result<(), CounterWriteError> {
         if self.get_enable() {
-            panic!("Tried to set the main counter value while the HPET was enabled");
+            return Err(CounterWriteError::CounterRunning);
         }
-        self.mmio
-            .as_mut_ptr()
+        self.regs_mut()
             .main_counter_value_register()
             .write(main_counter_value);
+        Ok(())
+    }
+
+    /// Get the time represented by the current main counter value.
+    ///
+    /// See [`Hpet::counter_value_to_duration`] for precision notes.
+    pub fn elapsed(&self) -> Duration {
+        self.counter_value_to_duration(self.main_counter_value())
+    }
+
+    /// Convert a main counter value (in ticks) into a [`Duration`].
+    ///
+    /// The HPET has femtosecond resolution, but [`Duration`] only has nanosecond resolution, so the result is truncated to the nanosecond.
+    pub fn counter_value_to_duration(&self, ticks: u64) -> Duration {
+        duration_for_ticks(self.main_counter_tick_period(), ticks)
+    }
+
+    /// Busy-waits for the given duration by polling the main counter.
+    ///
+    /// The HPET must be enabled. Durations longer than it takes for a 32-bit main counter to wrap are waited out in chunks of at most half the counter's range.
+    pub fn spin_delay(&self, duration: Duration) {
+        if !self.get_enable() {
+            panic!("Tried to spin delay while the HPET was disabled");
+        }
+        let mut remaining = self.duration_to_counter_value(duration);
+        let max_chunk = self.main_counter_mask() / 2;
+        while remaining > 0 {
+            let chunk = remaining.min(max_chunk);
+            self.spin_delay_ticks(chunk);
+            remaining -= chunk;
+        }
+    }
+
+    /// `ticks` must be less than the main counter's range, so that the elapsed ticks can't wrap past it
+    fn spin_delay_ticks(&self, ticks: u64) {
+        let mask = self.main_counter_mask();
+        let start = self.main_counter_value();
+        while self.main_counter_value().wrapping_sub(start) & mask < ticks {
+            core::hint::spin_loop();
+        }
+    }
+
+    /// Busy-waits until the main counter reaches or passes `target`. The HPET must be enabled.
+    ///
+    /// The main counter wrapping around is handled by treating `target` as in the past if it is more than half of the counter's range behind the main counter.
+    /// So `target` must be less than half of the counter's range ahead of the main counter (`2^31` ticks, about 2.5 minutes at 14.31818 MHz, on a 32-bit HPET).
+    pub fn wait_until_counter(&self, target: u64) {
+        if !self.get_enable() {
+            panic!("Tried to wait for the main counter while the HPET was disabled");
+        }
+        let mask = self.main_counter_mask();
+        loop {
+            let remaining = target.wrapping_sub(self.main_counter_value()) & mask;
+            if remaining == 0 || remaining > mask / 2 {
+                break;
+            }
+            core::hint::spin_loop();
+        }
+    }
+
+    /// The number of ticks until the main counter reaches the timer's comparator value, wrapping at the timer's effective width.
+    /// Returns `None` if the comparator value has already been passed, which is assumed if it is more than half of the wrapping range away (just like [`Hpet::wait_until_counter`]).
+    ///
+    /// Panics if the timer doesn't exist.
+    pub fn ticks_until(&self, timer_index: u8) -> Option<u64> {
+        let timer = self.timer(timer_index);
+        let mask = timer.max_one_shot_ticks() & self.main_counter_mask();
+        let comparator_value = timer.comparator_value();
+        let remaining = comparator_value.wrapping_sub(self.main_counter_value()) & mask;
+        if remaining > mask / 2 {
+            None
+        } else {
+            Some(remaining)
+        }
+    }
+
+    /// The longest period that the timer can be set to with [`HpetTimerMut::set_periodic`], based on the timer's effective width (see [`HpetTimerRef::effective_timer_width`]).
+    /// This can be different for each timer, because some timers can be 32-bit even if others are 64-bit.
+    ///
+    /// Panics if the timer doesn't exist.
+    pub fn timer_max_period(&self, index: u8) -> Duration {
+        self.timer(index).max_one_shot_duration()
+    }
+
+    /// The bits of the main counter that are used, based on [`Hpet::effective_counter_width`]
+    pub(crate) fn main_counter_mask(&self) -> u64 {
+        if self.effective_counter_width() == 64 {
+            u64::MAX
+        } else {
+            u32::MAX as u64
+        }
+    }
+
+    /// Rounds to the nearest tick, saturating at `u64::MAX`
+    fn duration_to_counter_value(&self, duration: Duration) -> u64 {
+        ticks_for_duration(self.main_counter_tick_period(), duration)
     }
 
     pub fn get_legacy_replacement_enabled(&self) -> bool {
-        self.mmio
-            .as_ptr()
-            .config()
+        self.regs().config().read().get_legacy_replacement_cnf()
+    }
+
+    /// Checks that LegacyReplacement Route is both supported and enabled.
+    /// On HPETs that don't support it, the enable bit can read back as set even though it has no effect.
+    pub fn legacy_replacement_active(&self) -> bool {
+        self.legacy_replacement_capable() && self.get_legacy_replacement_enabled()
+    }
+
+    /// When LegacyReplacement Route is enabled (and the HPET is enabled):
+    /// - Timer 0 will be routed to IRQ0 in Non-APIC or IRQ2 in the I/O APIC
+    /// - Timer 1 will be routed to IRQ8 in Non-APIC or IRQ8 in the I/O APIC
+    /// - Timers 2-n will be routed as per the routing in their own config
+    ///
+    /// **Note**: LegacyReplacement Route is not supported by all HPETs. Check [`Hpet::legacy_replacement_capable`] first.
+    pub fn set_legacy_replacement(&mut self, enable: bool) {
+        if !self.legacy_replacement_capable() {
+            panic!("LegacyReplacement Route not supported by this HPET");
+        }
+        self.regs_mut().config().update(|mut reg| {
+            reg.set_legacy_replacement_cnf(enable);
+            reg
+        });
+    }
+
+    /// Check if a timer's interrupt is active. This is only meaningful for level-triggered timers.
+    pub fn timer_interrupt_active(&self, index: u8) -> bool {
+        if index >= self.timers_count() {
+            panic!("Tried to access timer {index}, which is not supported by this HPET");
+        }
+        self.regs()
+            .interrupt_status()
             .read()
-            .get_legacy_replacement_cnf()
+            .get_t_n_int_sts(index as usize)
+            != 0
+    }
+
+    /// The indexes of the timers whose interrupts are active, from a single read of the interrupt status register.
+    /// This is only meaningful for level-triggered timers.
+    pub fn pending_interrupts(&self) -> impl Iterator<Item = u8> {
+        let interrupt_status = self.regs().interrupt_status().read();
+        (0..self.timers_count())
+            .filter(move |&index| interrupt_status.get_t_n_int_sts(index as usize) != 0)
+    }
+
+    /// Clear a level-triggered timer's interrupt. You need to do this from the interrupt handler, or else the interrupt will stay active.
+    pub fn clear_timer_interrupt(&mut self, index: u8) {
+        if index >= self.timers_count() {
+            panic!("Tried to access timer {index}, which is not supported by this HPET");
+        }
+        // Writing 0 to the other bits has no effect, so we don't read the register first
+        let mut reg = HpetGeneralInterruptStatusRegister(0);
+        reg.set_t_n_int_sts(index as usize, 1);
+        self.regs_mut().interrupt_status().write(reg);
+    }
+
+    /// The raw General Interrupt Status Register, where bit `n` is set if timer `n`'s interrupt is active.
+    /// This is only meaningful for level-triggered timers.
+    pub fn raw_interrupt_status(&self) -> u64 {
+        self.regs().interrupt_status().read().0
+    }
+
+    /// Clears the interrupts of every timer whose bit is set in `mask`, with a single write. This only affects level-triggered timers.
+    /// Bits for timers that don't exist are ignored, since the spec reserves them.
+    pub fn clear_interrupts_mask(&mut self, mask: u64) {
+        let mask = mask & self.timers_interrupt_mask();
+        self.regs_mut()
+            .interrupt_status()
+            .write(HpetGeneralInterruptStatusRegister(mask));
+    }
+
+    /// Clears the interrupts of every timer with a single write. This only affects level-triggered timers.
+    pub fn clear_all_interrupts(&mut self) {
+        self.clear_interrupts_mask(u64::MAX);
+    }
+
+    /// The bits of the General Interrupt Status Register that belong to timers that exist
+    fn timers_interrupt_mask(&self) -> u64 {
+        (1 << self.timers_count()) - 1
+    }
+
+    /// Disables the interrupts of every timer. This does not halt the main counter.
+    pub fn disable_all_timer_interrupts(&mut self) {
+        for index in 0..self.timers_count() {
+            self.timer_mut(index).set_interrupt_enable(false);
+        }
+    }
+
+    /// Sets the main counter to 0 and enables the HPET.
+    ///
+    /// **Note**: since the main counter is reset, previously captured main counter values (such as [`HpetInstant`]s) become meaningless.
+    pub fn start(&mut self) {
+        // The main counter must be halted before it can be written to
+        self.set_enable(false);
+        self.set_main_counter_value(0);
+        self.set_enable(true);
+    }
+
+    /// Disables the HPET, which halts the main counter and disables all timer interrupts
+    pub fn stop(&mut self) {
+        self.set_enable(false);
+    }
+
+    /// Makes the HPET act like the legacy PIT, sending interrupts to IRQ0 (IRQ2 on the I/O APIC) at `frequency_hz`:
+    /// - Enables LegacyReplacement Route
+    /// - Puts timer 0 in periodic mode, with the period rounded to the nearest tick
+    /// - Enables timer 0's interrupt
+    /// - Enables the HPET
+    ///
+    /// Everything is checked before any register is written, so if an error is returned, the HPET is left unchanged.
+    pub fn setup_legacy_pit(&mut self, frequency_hz: u32) -> Result<(), LegacyPitError> {
+        if !self.legacy_replacement_capable() {
+            return Err(LegacyPitError::Unsupported);
+        }
+        let timer = self.timer(0);
+        if !timer.supports_periodic_mode() {
+            return Err(LegacyPitError::PeriodicUnsupported);
+        }
+        let period_fs = self.main_counter_tick_period() as u128 * frequency_hz as u128;
+        let period_ticks = (1_000_000_000_000_000 + period_fs / 2)
+            .checked_div(period_fs)
+            .unwrap_or(0);
+        if period_ticks == 0 || period_ticks > timer.max_one_shot_ticks() as u128 {
+            return Err(LegacyPitError::InvalidFrequency(frequency_hz));
+        }
+        self.set_enable(false);
+        self.set_legacy_replacement(true);
+        let mut timer = self.timer_mut(0);
+        timer.set_periodic(period_ticks as u64);
+        timer.set_interrupt_enable(true);
+        self.set_enable(true);
+        Ok(())
+    }
+
+    /// Puts the HPET in a known state:
+    /// - The main counter is halted and set to 0
+    /// - LegacyReplacement Route is disabled
+    /// - All timer interrupts are disabled
+    pub fn reset(&mut self) {
+        // The main counter must be halted before it can be written to
+        self.set_enable(false);
+        self.set_main_counter_value(0);
+        self.regs_mut().config().update(|mut reg| {
+            reg.set_legacy_replacement_cnf(false);
+            reg
+        });
+        self.disable_all_timer_interrupts();
     }
 
     pub fn timers(&self) -> HpetTimersIterator {
@@ -114,36 +659,279 @@ impl Hpet<'_> {
         }
     }
 
+    /// Same as [`Hpet::timers`], but also gives the index of each timer
+    pub fn timers_enumerated(&self) -> impl Iterator<Item = (u8, HpetTimer)> {
+        self.timers().map(|timer| (timer.index, timer))
+    }
+
+    /// The timers for which `pred` returns `true`. Use [`HpetTimer::index`] to get the index of each timer.
+    pub fn timers_matching(
+        &self,
+        pred: impl Fn(&HpetTimer) -> bool,
+    ) -> impl Iterator<Item = HpetTimer> {
+        self.timers().filter(move |timer| pred(timer))
+    }
+
+    /// The index of the first timer that supports FSB interrupts
+    pub fn first_fsb_capable_timer(&self) -> Option<u8> {
+        (0..self.timers_count()).find(|&index| self.timer(index).supports_fsb_interrupts())
+    }
+
+    /// Whether at least one timer supports FSB interrupts
+    pub fn any_timer_supports_fsb(&self) -> bool {
+        self.first_fsb_capable_timer().is_some()
+    }
+
+    /// The index of the first timer that supports periodic mode
+    pub fn first_periodic_capable_timer(&self) -> Option<u8> {
+        (0..self.timers_count()).find(|&index| self.timer(index).supports_periodic_mode())
+    }
+
+    /// The index of the first timer that can send interrupts to the I/O APIC IRQ `irq`
+    pub fn first_timer_supporting_irq(&self, irq: u8) -> Option<u8> {
+        (0..self.timers_count()).find(|&index| self.timer(index).can_route_to_irq(irq))
+    }
+
+    /// Like [`Hpet::timers`], but lets you configure every timer
+    pub fn timers_mut(&mut self) -> HpetTimersMutIterator {
+        HpetTimersMutIterator {
+            timers_count: self.timers_count(),
+            fenced: self.fenced,
+            mmio: self.mmio.as_mut_ptr(),
+            periods_ticks: self.periods_ticks.iter_mut(),
+            index: 0,
+        }
+    }
+
+    /// Panics if the timer doesn't exist. See [`Hpet::try_timer`] for a non-panicking version.
     pub fn timer(&self, index: u8) -> HpetTimer {
-        if index >= self.timers_count() {
-            panic!("Tried to access timer {index}, which is not supported by this HPET");
+        self.try_timer(index).unwrap_or_else(|| {
+            panic!("Tried to access timer {index}, which is not supported by this HPET")
+        })
+    }
+
+    /// Returns `None` if the timer doesn't exist
+    pub fn try_timer(&self, index: u8) -> Option<HpetTimer> {
+        if index < self.timers_count() {
+            Some(HpetTimer { hpet: self, index })
+        } else {
+            None
         }
-        HpetTimer { hpet: self, index }
     }
 
+    /// Every pair of timers that are routed to the same I/O APIC IRQ, as `(timer_a, timer_b, shared_irq)` with `timer_a < timer_b`.
+    /// Timers in FSB mode and timers whose interrupt is disabled are skipped.
+    /// When LegacyReplacement Route is active (see [`Hpet::legacy_replacement_active`]), timers 0 and 1 are skipped too, since their routing bits are ignored.
+    pub fn irq_routing_conflicts(&self) -> impl Iterator<Item = (u8, u8, u8)> {
+        let legacy_replacement_active = self.legacy_replacement_active();
+        let routed_irq = move |index: u8| {
+            let timer = self.timer(index);
+            if (legacy_replacement_active && index < 2) || !timer.interrupt_enabled() {
+                None
+            } else {
+                timer.io_apic_irq()
+            }
+        };
+        (0..self.timers_count()).flat_map(move |timer_a| {
+            let irq = routed_irq(timer_a);
+            (timer_a + 1..self.timers_count()).filter_map(move |timer_b| {
+                let shared_irq = irq?;
+                (routed_irq(timer_b) == Some(shared_irq)).then_some((timer_a, timer_b, shared_irq))
+            })
+        })
+    }
+
+    /// The number of timers that have a capability
+    pub fn count_timers_supporting(&self, cap: TimerCapability) -> u8 {
+        self.timers()
+            .filter(|timer| timer.has_capability(cap))
+            .count() as u8
+    }
+
+    /// Checks if the timer has a capability, or returns `None` if the timer doesn't exist
+    pub fn timer_capability(&self, index: u8, cap: TimerCapability) -> Option<bool> {
+        self.try_timer(index).map(|timer| timer.has_capability(cap))
+    }
+
+    /// Panics if the timer doesn't exist. See [`Hpet::try_timer_mut`] for a non-panicking version.
     pub fn timer_mut<'a>(&'a mut self, index: u8) -> HpetTimerMut<'a> {
-        if index >= self.timers_count() {
-            panic!("Tried to access timer {index}, which is not supported by this HPET");
-        }
-        HpetTimerMut {
-            hpet: self.mmio.borrow_mut(),
-            index,
+        self.try_timer_mut(index).unwrap_or_else(|| {
+            panic!("Tried to access timer {index}, which is not supported by this HPET")
+        })
+    }
+
+    /// Returns `None` if the timer doesn't exist
+    pub fn try_timer_mut<'a>(&'a mut self, index: u8) -> Option<HpetTimerMut<'a>> {
+        if index < self.timers_count() {
+            Some(HpetTimerMut {
+                hpet: self.mmio.as_mut_ptr(),
+                index,
+                fenced: self.fenced,
+                period_ticks: &mut self.periods_ticks[index as usize],
+            })
+        } else {
+            None
         }
     }
 }
 
 impl Debug for Hpet<'_> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.fmt_debug(f, self.timers_count())
+    }
+}
+
+impl Hpet<'_> {
+    /// Same as the [`Debug`] impl, but only shows the first `max_timers` timers.
+    /// Use this if not all of the timers are mapped (see [`Hpet::required_mmio_size`]), for example if only a single page is mapped, since reading an unmapped timer would fault.
+    pub fn debug_safe(&self, max_timers: u8) -> impl Debug {
+        core::fmt::from_fn(move |f| self.fmt_debug(f, max_timers))
+    }
+
+    fn fmt_debug(&self, f: &mut core::fmt::Formatter<'_>, max_timers: u8) -> core::fmt::Result {
         f.debug_struct("HPET")
             .field("Supports 64-bit", &self.supports_64_bit_mode())
             .field("Tick Period (10^-15 s)", &self.main_counter_tick_period())
             .field("Counter Value", &self.main_counter_value())
             .field("Enabled", &self.get_enable())
-            .field_with("Timers", |f| f.debug_list().entries(self.timers()).finish())
+            .field_with("Timers", |f| {
+                f.debug_list()
+                    .entries(self.timers().take(max_timers as usize))
+                    .finish()
+            })
             .finish()
     }
 }
 
+fn serializing_fence() {
+    compiler_fence(Ordering::SeqCst);
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    unsafe {
+        core::arch::asm!("lfence", options(nostack, preserves_flags));
+    }
+    #[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+    core::sync::atomic::fence(Ordering::SeqCst);
+    compiler_fence(Ordering::SeqCst);
+}
+
+/// A pointer to the HPET's memory that does a memory fence when it is created and when it is dropped, if the HPET was created with [`Hpet::new_with_fences`].
+/// It derefs to the pointer. Keep it alive until after the access (for example by accessing the memory in the same statement), so that there is a fence before and after the access.
+pub struct Fenced<P> {
+    ptr: P,
+    fenced: bool,
+}
+
+impl<P> Fenced<P> {
+    pub(crate) fn new(ptr: P, fenced: bool) -> Self {
+        if fenced {
+            memory_fence();
+        }
+        Self { ptr, fenced }
+    }
+}
+
+impl<P> Deref for Fenced<P> {
+    type Target = P;
+
+    fn deref(&self) -> &P {
+        &self.ptr
+    }
+}
+
+impl<P> Drop for Fenced<P> {
+    fn drop(&mut self) {
+        if self.fenced {
+            memory_fence();
+        }
+    }
+}
+
+/// Orders all memory accesses before the fence with the ones after it, and flushes write-combining buffers
+fn memory_fence() {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    unsafe {
+        core::arch::asm!("mfence", options(nostack, preserves_flags));
+    }
+    #[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+    core::sync::atomic::fence(Ordering::SeqCst);
+}
+
+/// Human-readable summary, for example `HPET @ 14.318 MHz (69.84 ns), 64-bit, 3 timers, enabled`
+impl Display for Hpet<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let timers_count = self.timers_count();
+        if self.main_counter_tick_period() == 0 {
+            write!(f, "HPET @ invalid period")?;
+        } else {
+            write!(
+                f,
+                "HPET @ {:.3} MHz ({:.2} ns)",
+                self.frequency_mhz(),
+                self.tick_period_ns()
+            )?;
+        }
+        write!(
+            f,
+            ", {}-bit, {} {}, {}",
+            self.effective_counter_width(),
+            timers_count,
+            if timers_count == 1 { "timer" } else { "timers" },
+            if self.get_enable() {
+                "enabled"
+            } else {
+                "disabled"
+            }
+        )
+    }
+}
+
+/// A snapshot of the HPET's capabilities, from [`Hpet::capabilities`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HpetCapabilities {
+    pub vendor_id: u16,
+    /// Same as [`Hpet::timers_count`]
+    pub num_timers: u8,
+    /// Same as [`Hpet::main_counter_tick_period`]
+    pub tick_period_fs: u32,
+    pub legacy_replacement_capable: bool,
+    pub supports_64_bit: bool,
+    pub revision_id: u8,
+}
+
+/// A snapshot of the HPET's configuration, from [`Hpet::config_snapshot`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HpetConfig {
+    /// Same as [`Hpet::get_enable`]
+    pub enabled: bool,
+    /// Same as [`Hpet::get_legacy_replacement_enabled`]
+    pub legacy_replacement: bool,
+}
+
+/// A snapshot of a timer's Configuration and Capability Register, from [`HpetTimerRef::config_snapshot`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimerConfigSnapshot {
+    /// Same as [`HpetTimerRef::io_apic_route_caps`]
+    pub route_caps: IrqRouteCaps,
+    /// Same as [`HpetTimerRef::supports_fsb_interrupts`]
+    pub supports_fsb: bool,
+    /// Whether interrupts are sent as FSB messages instead of through the I/O APIC
+    pub fsb_enabled: bool,
+    /// The I/O APIC IRQ that interrupts are sent to, if FSB is not enabled
+    pub io_apic_irq: u8,
+    /// Same as [`HpetTimerRef::is_forced_32_bit`]
+    pub forced_32_bit: bool,
+    /// Same as [`HpetTimerRef::supports_64_bit_mode`]
+    pub supports_64_bit: bool,
+    /// Same as [`HpetTimerRef::supports_periodic_mode`]
+    pub supports_periodic: bool,
+    /// Same as [`HpetTimerRef::is_periodic`]
+    pub periodic: bool,
+    /// Same as [`HpetTimerRef::interrupt_enabled`]
+    pub interrupt_enabled: bool,
+    /// Same as [`HpetTimerRef::interrupt_trigger`]
+    pub trigger: InterruptTrigger,
+}
+
 pub struct HpetTimersIterator<'a> {
     mmio: &'a Hpet<'a>,
     index: u8,
@@ -165,6 +953,43 @@ impl<'a> Iterator for HpetTimersIterator<'a> {
     }
 }
 
+pub struct HpetTimersMutIterator<'a> {
+    mmio: VolatilePtr<'a, HpetMemory>,
+    periods_ticks: core::slice::IterMut<'a, u64>,
+    timers_count: u8,
+    index: u8,
+    fenced: bool,
+}
+
+impl<'a> Iterator for HpetTimersMutIterator<'a> {
+    type Item = HpetTimerMut<'a>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index < self.timers_count {
+            // Each `HpetTimerMut` only writes to its own timer's registers, so it's fine for them to exist at the same time
+            let hpet_timer = HpetTimerMut {
+                hpet: self.mmio,
+                index: self.index,
+                fenced: self.fenced,
+                period_ticks: self.periods_ticks.next()?,
+            };
+            self.index += 1;
+            Some(hpet_timer)
+        } else {
+            None
+        }
+    }
+}
+
+/// Lets you write `for timer in &mut hpet { ... }`. Same as [`Hpet::timers_mut`].
+impl<'a> IntoIterator for &'a mut Hpet<'_> {
+    type Item = HpetTimerMut<'a>;
+    type IntoIter = HpetTimersMutIterator<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.timers_mut()
+    }
+}
+
 pub struct HpetTimer<'a> {
     hpet: &'a Hpet<'a>,
     index: u8,
@@ -180,65 +1005,166 @@ impl Debug for HpetTimer<'_> {
                 "Supported I/O APIC Interrupts",
                 &format_args!("{:b}", self.supported_io_apic_interrupts()),
             )
+            .field("Interrupt Enabled", &self.interrupt_enabled())
+            .field("Periodic", &self.is_periodic())
+            .field("Trigger", &self.interrupt_trigger())
+            .field_with("Route", |f| match self.fsb_route() {
+                Some((address, data)) => write!(f, "FSB {address:#x} <- {data:#x}"),
+                None => write!(f, "I/O APIC IRQ {}", self.io_apic_irq().unwrap_or_default()),
+            })
+            .field("Comparator Value", &self.comparator_value())
             .finish()
     }
 }
 
-impl HpetTimerRef for HpetTimer<'_> {
-    #[allow(private_interfaces)]
-    fn hpet_timer(&self) -> VolatilePtr<HpetTimerMemory, ReadOnly> {
+impl HpetTimer<'_> {
+    /// The index of this timer, which can be passed to [`Hpet::timer`] or [`Hpet::timer_mut`]
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+
+    /// The period that the timer was put in periodic mode with, or `None` if the timer isn't in periodic mode.
+    /// The HPET does not let you read back the period, so this is the period that was last set with [`HpetTimerMut::set_periodic`] (or [`Hpet::setup_legacy_pit`]) through this [`Hpet`].
+    /// Returns `None` if the period isn't known, for example if the timer was put in periodic mode before this [`Hpet`] was created.
+    pub fn period_ticks(&self) -> Option<u64> {
+        if !self.is_periodic() {
+            return None;
+        }
+        Some(self.hpet.periods_ticks[self.index as usize]).filter(|&period_ticks| period_ticks != 0)
+    }
+
+    /// The rate that a periodic timer fires at, rounded to the nearest Hz, based on [`HpetTimer::period_ticks`].
+    /// This can be different from the requested rate, because the period is a whole number of ticks.
+    /// Returns `None` if the timer isn't in periodic mode, or if the period isn't known.
+    /// This does not wait, so it works even if the HPET is disabled. See [`HpetTimer::measure_periodic_frequency_hz`] to measure the rate instead.
+    pub fn periodic_frequency_hz(&self) -> Option<u64> {
+        self.period_ticks()
+            .and_then(|period_ticks| self.frequency_hz_for_period(period_ticks))
+    }
+
+    fn frequency_hz_for_period(&self, period_ticks: u64) -> Option<u64> {
+        let period_fs = period_ticks as u128 * self.hpet.main_counter_tick_period() as u128;
+        (1_000_000_000_000_000 + period_fs / 2)
+            .checked_div(period_fs)
+            .map(|frequency_hz| frequency_hz as u64)
+    }
+
+    /// Blocks until the timer fires once (or `timeout` passes) to measure the rate that a periodic timer fires at, rounded to the nearest Hz.
+    /// Returns `None` if the timer isn't in periodic mode, the HPET is disabled, or the timer didn't fire within `timeout`.
+    /// This can be different from the requested rate, because the period is a whole number of ticks.
+    ///
+    /// **Note**: the HPET does not let you read back the period, so this measures it by waiting for the comparator value to advance.
+    pub fn measure_periodic_frequency_hz(&self, timeout: Duration) -> Option<u64> {
+        if !self.is_periodic() || !self.hpet.get_enable() {
+            return None;
+        }
+        let timeout_ticks = self.hpet.duration_to_counter_value(timeout);
+        let mask = self.hpet.main_counter_mask();
+        let previous_deadline = self.comparator_value();
+        let mut last_counter_value = self.hpet.main_counter_value();
+        let mut elapsed_ticks = 0u64;
+        let next_deadline = loop {
+            let comparator_value = self.comparator_value();
+            if comparator_value != previous_deadline {
+                break comparator_value;
+            }
+            if elapsed_ticks >= timeout_ticks {
+                return None;
+            }
+            core::hint::spin_loop();
+            let counter_value = self.hpet.main_counter_value();
+            elapsed_ticks =
+                elapsed_ticks.saturating_add(counter_value.wrapping_sub(last_counter_value) & mask);
+            last_counter_value = counter_value;
+        };
+        let period_ticks =
+            next_deadline.wrapping_sub(previous_deadline) & self.max_one_shot_ticks();
+        self.frequency_hz_for_period(period_ticks)
+    }
+
+    /// The longest time that a one-shot can be armed for with a single comparator value, based on [`HpetTimerRef::max_one_shot_ticks`]
+    pub fn max_one_shot_duration(&self) -> Duration {
         self.hpet
-            .mmio
-            .as_ptr()
-            .timers()
-            .as_slice()
-            .index(self.index as usize)
+            .counter_value_to_duration(self.max_one_shot_ticks())
+    }
+}
+
+impl HpetTimerRef for HpetTimer<'_> {
+    fn hpet_timer(&self) -> Fenced<VolatilePtr<'_, HpetTimerMemory, ReadOnly>> {
+        self.hpet.timer_regs(self.index)
     }
 }
 
 pub struct HpetTimerMut<'a> {
-    hpet: VolatileRef<'a, HpetMemory>,
+    hpet: VolatilePtr<'a, HpetMemory>,
     index: u8,
+    /// Set if the HPET was created with [`Hpet::new_with_fences`]
+    fenced: bool,
+    /// This timer's entry in [`Hpet`]'s recorded periods
+    period_ticks: &'a mut u64,
 }
 
 impl HpetTimerRef for HpetTimerMut<'_> {
-    #[allow(private_interfaces)]
-    fn hpet_timer(&self) -> VolatilePtr<HpetTimerMemory, ReadOnly> {
-        self.hpet
-            .as_ptr()
-            .timers()
-            .as_slice()
-            .index(self.index as usize)
+    fn hpet_timer(&self) -> Fenced<VolatilePtr<'_, HpetTimerMemory, ReadOnly>> {
+        Fenced::new(
+            self.hpet
+                .read_only()
+                .timers()
+                .as_slice()
+                .index(self.index as usize),
+            self.fenced,
+        )
     }
 }
 
+#[derive(Debug, Clone, Copy)]
 pub enum InterruptConfig {
     IoApic(u8),
     Fsb(TimerNFsbInterruptRouteRegister),
 }
 
 impl HpetTimerMut<'_> {
-    fn timer_mut(&mut self) -> VolatilePtr<HpetTimerMemory> {
-        self.hpet
-            .as_mut_ptr()
-            .timers()
-            .as_slice()
-            .index(self.index as usize)
+    /// The HPET's memory, with fences around the access if the HPET was created with [`Hpet::new_with_fences`]
+    fn regs(&self) -> Fenced<VolatilePtr<'_, HpetMemory>> {
+        Fenced::new(self.hpet, self.fenced)
+    }
+
+    fn timer_mut(&mut self) -> Fenced<VolatilePtr<'_, HpetTimerMemory>> {
+        Fenced::new(
+            self.hpet.timers().as_slice().index(self.index as usize),
+            self.fenced,
+        )
     }
 
     /// **Note**
     /// - Not all I/O APIC irqs are guaranteed to be supported.
     /// - FSB is not guaranteed to be supported.
+    ///
+    /// See [`HpetTimerMut::try_configure_interrupt`] for a non-panicking version.
     pub fn configure_interrupt(&mut self, interrupt_config: InterruptConfig) {
+        if let Err(error) = self.try_configure_interrupt(interrupt_config) {
+            match error {
+                InterruptConfigError::UnsupportedIrq(irq) => panic!("Unsupported IRQ {irq}"),
+                InterruptConfigError::FsbUnsupported => {
+                    panic!("FSB interrupts not supported by this timer")
+                }
+            }
+        }
+    }
+
+    /// Same as [`HpetTimerMut::configure_interrupt`], but returns an error instead of panicking if the interrupt config is not supported.
+    /// If an error is returned, the timer is left unchanged.
+    pub fn try_configure_interrupt(
+        &mut self,
+        interrupt_config: InterruptConfig,
+    ) -> Result<(), InterruptConfigError> {
+        self.check_interrupt_config(&interrupt_config)?;
         match interrupt_config {
             InterruptConfig::IoApic(irq) => {
                 self.timer_mut()
                     .configuration_and_capability_register()
                     .update(|mut reg| {
                         reg.set_fsb_en_cnf(false);
-                        if reg.get_int_route_cap() & (1 << irq) == 0 {
-                            panic!("Unsupported IRQ");
-                        }
                         reg.set_int_route_cnf(irq);
                         reg
                     });
@@ -247,15 +1173,40 @@ impl HpetTimerMut<'_> {
                 self.timer_mut()
                     .configuration_and_capability_register()
                     .update(|mut reg| {
-                        if !reg.get_fsb_int_del_cap() {
-                            panic!("FSB interrupts not supported by this timer");
-                        }
                         reg.set_fsb_en_cnf(true);
                         reg
                     });
                 self.timer_mut().fsb_interrupt_route_register().write(fsb);
             }
         }
+        Ok(())
+    }
+
+    /// Routes the timer's interrupts to the lowest-numbered I/O APIC IRQ that it supports, and returns that IRQ.
+    /// Returns `None` (and leaves the timer unchanged) if the timer doesn't support any I/O APIC IRQs.
+    pub fn auto_route_io_apic(&mut self) -> Option<u8> {
+        let irq = self.io_apic_route_caps().iter().next()?;
+        self.configure_interrupt(InterruptConfig::IoApic(irq));
+        Some(irq)
+    }
+
+    pub(crate) fn check_interrupt_config(
+        &self,
+        interrupt_config: &InterruptConfig,
+    ) -> Result<(), InterruptConfigError> {
+        match *interrupt_config {
+            InterruptConfig::IoApic(irq) => {
+                if !self.can_route_to_irq(irq) {
+                    return Err(InterruptConfigError::UnsupportedIrq(irq));
+                }
+            }
+            InterruptConfig::Fsb(_) => {
+                if !self.supports_fsb_interrupts() {
+                    return Err(InterruptConfigError::FsbUnsupported);
+                }
+            }
+        }
+        Ok(())
     }
 
     pub fn set_interrupt_enable(&mut self, enable: bool) {
@@ -267,16 +1218,232 @@ impl HpetTimerMut<'_> {
             });
     }
 
+    /// Disables the timer's interrupt, without changing the rest of its configuration.
+    /// Pass the returned state to [`HpetTimerMut::unmask`] to put the interrupt back to how it was.
+    pub fn mask(&mut self) -> TimerInterruptState {
+        let state = TimerInterruptState {
+            enabled: self.interrupt_enabled(),
+        };
+        self.set_interrupt_enable(false);
+        state
+    }
+
+    /// Restores the interrupt enable bit that was saved by [`HpetTimerMut::mask`]
+    pub fn unmask(&mut self, state: TimerInterruptState) {
+        self.set_interrupt_enable(state.enabled);
+    }
+
+    /// Same as [`HpetTimerMut::set_interrupt_enable`], but reads the bit back afterwards.
+    /// Returns `true` if the write took effect.
+    pub fn set_interrupt_enable_verified(&mut self, enable: bool) -> bool {
+        self.set_interrupt_enable(enable);
+        self.interrupt_enabled() == enable
+    }
+
+    pub fn set_interrupt_trigger(&mut self, trigger: InterruptTrigger) {
+        self.timer_mut()
+            .configuration_and_capability_register()
+            .update(|mut reg| {
+                reg.set_int_type_cnf(match trigger {
+                    InterruptTrigger::Edge => false,
+                    InterruptTrigger::Level => true,
+                });
+                reg
+            });
+    }
+
+    /// Force a 64-bit timer to behave as a 32-bit timer. This is useful if you can't do atomic 64-bit reads and writes.
+    ///
+    /// If the timer is 32-bit, this has no effect.
+    pub fn set_force_32_bit(&mut self, force: bool) {
+        self.timer_mut()
+            .configuration_and_capability_register()
+            .update(|mut reg| {
+                reg.set_32_mode_cnf(force);
+                reg
+            });
+    }
+
+    /// The value is masked to the timer's effective width (see [`HpetTimerRef::effective_timer_width`]), so on a 32-bit timer only the low 32 bits are written.
+    /// In debug builds, this panics if any high bits would be discarded, since that usually means that the deadline was computed for a 64-bit timer.
     pub fn set_comparator_value(&mut self, comparator_value: u64) {
+        let mask = self.comparator_mask();
+        debug_assert!(
+            comparator_value & !mask == 0,
+            "Comparator value {comparator_value:#x} does not fit in this 32-bit timer"
+        );
         self.timer_mut()
             .comparator_register()
-            .write(comparator_value);
+            .write(comparator_value & mask);
+    }
+
+    /// Same as [`HpetTimerMut::set_comparator_value`], but uses 32-bit writes (the low 32 bits, then the high 32 bits) instead of one 64-bit write.
+    /// Use this if you can't do 64-bit writes, such as on a 32-bit CPU. If the timer's effective width is 32, only the low 32 bits are written.
+    ///
+    /// **Note**: between the two writes, the comparator holds a mix of the old and new values, which could match the main counter.
+    /// To avoid an unexpected interrupt, disable the timer's interrupt or halt the main counter first.
+    pub fn write_comparator_split(&mut self, value: u64) {
+        let is_64_bit = self.effective_timer_width() == 64;
+        {
+            // The guard has to live until after the write, so that the fence after the write happens
+            let timer = self.timer_mut();
+            // Safety: the low 32 bits are at the start of the register (little endian)
+            unsafe { timer.comparator_register().map(|ptr| ptr.cast::<u32>()) }.write(value as u32);
+        }
+        if is_64_bit {
+            let timer = self.timer_mut();
+            // Safety: the high 32 bits are right after the low 32 bits (little endian)
+            unsafe {
+                timer
+                    .comparator_register()
+                    .map(|ptr| ptr.cast::<u32>().add(1))
+            }
+            .write((value >> 32) as u32);
+        }
+    }
+
+    /// Puts the timer in periodic mode, firing every `period_ticks` main counter ticks, starting `period_ticks` from now.
+    ///
+    /// **Note**: Periodic mode is not supported by all timers. Check [`HpetTimerRef::supports_periodic_mode`] first.
+    pub fn set_periodic(&mut self, period_ticks: u64) {
+        self.timer_mut()
+            .configuration_and_capability_register()
+            .update(|mut reg| {
+                if !reg.get_per_int_cp() {
+                    panic!("Periodic mode not supported by this timer");
+                }
+                reg.set_type_cnf(true);
+                reg.set_val_set_cnf(true);
+                reg
+            });
+        let first_deadline = self.next_deadline(self.main_counter_value(), period_ticks);
+        // After setting `Tn_VAL_SET_CNF`, the first write sets the time of the next interrupt,
+        // and the second write sets the accumulator (the period)
+        self.timer_mut().comparator_register().write(first_deadline);
+        self.timer_mut().comparator_register().write(period_ticks);
+        *self.period_ticks = period_ticks;
+    }
+
+    /// Same as [`HpetTimerMut::set_periodic`], but returns an error instead of panicking if periodic mode is not supported,
+    /// or if the period does not fit in the timer's effective width.
+    pub fn set_periodic_checked(&mut self, period_ticks: u64) -> Result<(), PeriodError> {
+        if !self.supports_periodic_mode() {
+            return Err(PeriodError::Unsupported);
+        }
+        if self.effective_timer_width() == 32 && period_ticks > u32::MAX as u64 {
+            return Err(PeriodError::TooLargeFor32Bit);
+        }
+        self.set_periodic(period_ticks);
+        Ok(())
+    }
+
+    /// Re-phases a periodic timer so that its next interrupt happens when the main counter reaches `next_value`, without changing the period.
+    ///
+    /// This sets `Tn_VAL_SET_CNF` and writes the comparator once.
+    /// After setting `Tn_VAL_SET_CNF`, the first comparator write sets the time of the next interrupt, and a second write would set the period,
+    /// so don't write the comparator again unless you also want to change the period.
+    pub fn reset_periodic_accumulator(&mut self, next_value: u64) {
+        self.timer_mut()
+            .configuration_and_capability_register()
+            .update(|mut reg| {
+                reg.set_val_set_cnf(true);
+                reg
+            });
+        self.timer_mut().comparator_register().write(next_value);
+    }
+
+    /// Puts the timer in one-shot (non-periodic) mode
+    pub fn set_one_shot(&mut self) {
+        self.timer_mut()
+            .configuration_and_capability_register()
+            .update(|mut reg| {
+                reg.set_type_cnf(false);
+                reg
+            });
+        *self.period_ticks = 0;
+    }
+
+    /// Puts the timer in one-shot mode, firing `ticks` main counter ticks from now.
+    ///
+    /// **Note**: if the HPET is not enabled, the main counter is not advancing, so the timer will fire `ticks` after the HPET gets enabled.
+    pub fn arm_one_shot_after(&mut self, ticks: u64) {
+        self.set_one_shot();
+        let main_counter_value = self.main_counter_value();
+        self.set_comparator_value(self.next_deadline(main_counter_value, ticks));
+    }
+
+    /// Software periodic mode, for timers that don't support periodic mode. Call this from the interrupt handler with the current main counter value.
+    /// If `hpet_counter` has passed the comparator value, the comparator is advanced by `period_ticks` and `true` is returned.
+    /// If the handler was late and more than one period was missed, the comparator is advanced by as many periods as needed to be after `hpet_counter`.
+    ///
+    /// Values are compared at the timer's effective width (see [`HpetTimerRef::effective_timer_width`]), just like [`Hpet::wait_until_counter`].
+    pub fn rearm_if_fired(&mut self, period_ticks: u64, hpet_counter: u64) -> bool {
+        if period_ticks == 0 {
+            panic!("Tried to rearm a timer with a period of 0 ticks");
+        }
+        let mask = self.comparator_mask();
+        let comparator_value = self.comparator_value() & mask;
+        let elapsed = hpet_counter.wrapping_sub(comparator_value) & mask;
+        if elapsed > mask / 2 {
+            return false;
+        }
+        let periods = elapsed / period_ticks + 1;
+        self.set_comparator_value(
+            self.next_deadline(comparator_value, periods.wrapping_mul(period_ticks)),
+        );
+        true
+    }
+
+    /// Same as [`HpetTimerMut::arm_one_shot_after`], but takes a [`Duration`], which is rounded to the nearest tick.
+    ///
+    /// Returns an error if the duration is too long for the timer's comparator (more than `u32::MAX` ticks on a 32-bit timer).
+    pub fn arm_one_shot_after_duration(&mut self, duration: Duration) -> Result<(), ArmError> {
+        let ticks = ticks_for_duration_u128(self.main_counter_tick_period(), duration);
+        if ticks > self.comparator_mask() as u128 {
+            return Err(ArmError::DurationTooLong);
+        }
+        self.arm_one_shot_after(ticks as u64);
+        Ok(())
+    }
+
+    /// Same as [`HpetTimerMut::arm_one_shot_after`], but checks that the main counter did not already pass the deadline by the time the comparator was written.
+    /// If it did, the timer will not fire until the counter wraps around, so you should re-arm it (or handle the deadline right away).
+    pub fn try_arm_one_shot_after(&mut self, ticks: u64) -> Result<(), ArmError> {
+        self.set_one_shot();
+        let main_counter_value = self.main_counter_value();
+        self.try_arm_one_shot_from(main_counter_value, ticks)
+    }
+
+    /// Arms the (already one-shot) timer to fire `ticks` after the main counter value `start`, checking that the counter did not already pass the deadline
+    pub(crate) fn try_arm_one_shot_from(&mut self, start: u64, ticks: u64) -> Result<(), ArmError> {
+        self.set_comparator_value(self.next_deadline(start, ticks));
+        let elapsed = self.main_counter_value().wrapping_sub(start) & self.comparator_mask();
+        if elapsed >= ticks {
+            return Err(ArmError::DeadlineInPast);
+        }
+        Ok(())
+    }
+
+    /// The bits of the comparator that are used, based on [`HpetTimerRef::effective_timer_width`]
+    fn comparator_mask(&self) -> u64 {
+        self.max_one_shot_ticks()
+    }
+
+    pub(crate) fn main_counter_tick_period(&self) -> u32 {
+        self.regs()
+            .capabilities_and_id()
+            .read()
+            .get_counter_clk_period()
+    }
+
+    fn main_counter_value(&self) -> u64 {
+        self.regs().main_counter_value_register().read()
     }
 }
 
 pub trait HpetTimerRef {
-    #[allow(private_interfaces)]
-    fn hpet_timer(&self) -> VolatilePtr<HpetTimerMemory, ReadOnly>;
+    /// The timer's registers. Keep the returned guard until after the access, so that [`Hpet::new_with_fences`] can fence after it.
+    fn hpet_timer(&self) -> Fenced<VolatilePtr<'_, HpetTimerMemory, ReadOnly>>;
 
     fn supported_io_apic_interrupts(&self) -> u32 {
         self.hpet_timer()
@@ -285,6 +1452,21 @@ pub trait HpetTimerRef {
             .get_int_route_cap()
     }
 
+    /// The I/O APIC IRQs that this timer can send interrupts to, from lowest to highest
+    fn supported_io_apic_irqs(&self) -> impl Iterator<Item = u8> {
+        self.io_apic_route_caps().iter()
+    }
+
+    /// Same as [`HpetTimerRef::supported_io_apic_interrupts`], but as an [`IrqRouteCaps`]
+    fn io_apic_route_caps(&self) -> IrqRouteCaps {
+        IrqRouteCaps(self.supported_io_apic_interrupts())
+    }
+
+    /// Checks if this timer can send interrupts to the I/O APIC IRQ `irq`. IRQs 32 and above are never supported.
+    fn can_route_to_irq(&self, irq: u8) -> bool {
+        self.io_apic_route_caps().supports(irq)
+    }
+
     fn supports_fsb_interrupts(&self) -> bool {
         self.hpet_timer()
             .configuration_and_capability_register()
@@ -306,6 +1488,113 @@ pub trait HpetTimerRef {
             .get_per_int_cp()
     }
 
+    /// This is always `false` for 32-bit timers.
+    fn is_forced_32_bit(&self) -> bool {
+        self.hpet_timer()
+            .configuration_and_capability_register()
+            .read()
+            .get_32_mode_cnf()
+    }
+
+    /// The width of the timer's comparator in bits, either 32 or 64.
+    /// This is 32 if the timer is 32-bit, or if it is a 64-bit timer that is forced into 32-bit mode.
+    fn effective_timer_width(&self) -> u8 {
+        let reg = self
+            .hpet_timer()
+            .configuration_and_capability_register()
+            .read();
+        if reg.get_size_cap() && !reg.get_32_mode_cnf() {
+            64
+        } else {
+            32
+        }
+    }
+
+    /// The most ticks that a one-shot can be armed for with a single comparator value, which is `2^width - 1` based on [`HpetTimerRef::effective_timer_width`]
+    fn max_one_shot_ticks(&self) -> u64 {
+        if self.effective_timer_width() == 64 {
+            u64::MAX
+        } else {
+            u32::MAX as u64
+        }
+    }
+
+    /// Computes the comparator value for a deadline `delta` ticks after `now`, wrapping at the timer's effective width (see [`HpetTimerRef::effective_timer_width`])
+    fn next_deadline(&self, now: u64, delta: u64) -> u64 {
+        let deadline = now.wrapping_add(delta);
+        if self.effective_timer_width() == 64 {
+            deadline
+        } else {
+            deadline & u32::MAX as u64
+        }
+    }
+
+    /// Note that if the timer is 32-bit, only the lower 32 bits of the value are meaningful.
+    fn comparator_value(&self) -> u64 {
+        self.hpet_timer().comparator_register().read()
+    }
+
+    /// Reads the Configuration and Capability Register once, so that all of the fields are from the same point in time
+    fn config_snapshot(&self) -> TimerConfigSnapshot {
+        let reg = self
+            .hpet_timer()
+            .configuration_and_capability_register()
+            .read();
+        TimerConfigSnapshot {
+            route_caps: IrqRouteCaps(reg.get_int_route_cap()),
+            supports_fsb: reg.get_fsb_int_del_cap(),
+            fsb_enabled: reg.get_fsb_en_cnf(),
+            io_apic_irq: reg.get_int_route_cnf(),
+            forced_32_bit: reg.get_32_mode_cnf(),
+            supports_64_bit: reg.get_size_cap(),
+            supports_periodic: reg.get_per_int_cp(),
+            periodic: reg.get_type_cnf(),
+            interrupt_enabled: reg.get_int_enb_cnf(),
+            trigger: if reg.get_int_type_cnf() {
+                InterruptTrigger::Level
+            } else {
+                InterruptTrigger::Edge
+            },
+        }
+    }
+
+    /// Checks one of the timer's capabilities
+    fn has_capability(&self, cap: TimerCapability) -> bool {
+        match cap {
+            TimerCapability::Fsb => self.supports_fsb_interrupts(),
+            TimerCapability::Periodic => self.supports_periodic_mode(),
+            TimerCapability::SixtyFourBit => self.supports_64_bit_mode(),
+        }
+    }
+
+    /// Whether the timer is in periodic mode (`true`) or one-shot mode (`false`)
+    fn is_periodic(&self) -> bool {
+        self.hpet_timer()
+            .configuration_and_capability_register()
+            .read()
+            .get_type_cnf()
+    }
+
+    fn interrupt_enabled(&self) -> bool {
+        self.hpet_timer()
+            .configuration_and_capability_register()
+            .read()
+            .get_int_enb_cnf()
+    }
+
+    fn interrupt_trigger(&self) -> InterruptTrigger {
+        if self
+            .hpet_timer()
+            .configuration_and_capability_register()
+            .read()
+            .get_int_type_cnf()
+        {
+            InterruptTrigger::Level
+        } else {
+            InterruptTrigger::Edge
+        }
+    }
+
     fn interrupt_mode(&self) -> InterruptMode {
         if self
             .hpet_timer()
@@ -318,6 +1607,41 @@ pub trait HpetTimerRef {
             InterruptMode::IoApic
         }
     }
+
+    /// The FSB interrupt message `(address, data)` that the timer sends, or `None` if the timer is in I/O APIC mode
+    fn fsb_route(&self) -> Option<(u32, u32)> {
+        match self.interrupt_mode() {
+            InterruptMode::Fsb => {
+                let reg = self.hpet_timer().fsb_interrupt_route_register().read();
+                Some((reg.fsb_int_addr(), reg.fsb_int_val()))
+            }
+            InterruptMode::IoApic => None,
+        }
+    }
+
+    /// The I/O APIC IRQ that interrupts are sent to, or `None` if the timer is in FSB mode
+    fn io_apic_irq(&self) -> Option<u8> {
+        let reg = self
+            .hpet_timer()
+            .configuration_and_capability_register()
+            .read();
+        if reg.get_fsb_en_cnf() {
+            None
+        } else {
+            Some(reg.get_int_route_cnf())
+        }
+    }
+}
+
+/// A capability that a timer may or may not have, for use with [`Hpet::timer_capability`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimerCapability {
+    /// Same as [`HpetTimerRef::supports_fsb_interrupts`]
+    Fsb,
+    /// Same as [`HpetTimerRef::supports_periodic_mode`]
+    Periodic,
+    /// Same as [`HpetTimerRef::supports_64_bit_mode`]
+    SixtyFourBit,
 }
 
 #[derive(Debug)]
@@ -327,3 +1651,134 @@ pub enum InterruptMode {
     /// Interrupts are directly sent to a Local APIC
     Fsb,
 }
+
+/// Whether a timer's interrupt was enabled before it was masked with [`HpetTimerMut::mask`]
+#[must_use]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimerInterruptState {
+    enabled: bool,
+}
+
+impl TimerInterruptState {
+    pub fn was_enabled(&self) -> bool {
+        self.enabled
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InterruptTrigger {
+    /// An edge is generated for every interrupt. No need to clear the interrupt status.
+    Edge,
+    /// The interrupt is held active until it is cleared with [`Hpet::clear_timer_interrupt`].
+    Level,
+}
+
+/// The I/O APIC IRQs that a timer can send interrupts to. Bit *n* is set if IRQ *n* is supported.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct IrqRouteCaps(pub u32);
+
+impl IrqRouteCaps {
+    pub fn supports(&self, irq: u8) -> bool {
+        irq < 32 && self.0 & (1 << irq) != 0
+    }
+
+    /// The supported IRQs, from lowest to highest
+    pub fn iter(self) -> impl Iterator<Item = u8> {
+        (0..32).filter(move |&irq| self.supports(irq))
+    }
+}
+
+impl Debug for IrqRouteCaps {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn next_deadline_wraps_64_bit() {
+        let mut backing = HpetBacking::new();
+        let hpet = Hpet::from_backing(&mut backing);
+        let timer = hpet.timer(0);
+        assert_eq!(timer.effective_timer_width(), 64);
+        assert_eq!(timer.next_deadline(100, 5), 105);
+        assert_eq!(timer.next_deadline(u32::MAX as u64, 1), 1 << 32);
+        assert_eq!(timer.next_deadline(u64::MAX - 1, 5), 3);
+    }
+
+    #[test]
+    fn next_deadline_wraps_32_bit() {
+        let mut backing = HpetBacking::new();
+        let mut hpet = Hpet::from_backing(&mut backing);
+        hpet.timer_mut(0).set_force_32_bit(true);
+        let timer = hpet.timer(0);
+        assert_eq!(timer.effective_timer_width(), 32);
+        assert_eq!(timer.next_deadline(100, 5), 105);
+        assert_eq!(timer.next_deadline(0xFFFF_FFFE, 5), 3);
+        assert_eq!(timer.next_deadline(u64::MAX - 1, 5), 3);
+    }
+
+    #[test]
+    fn irq_routing_conflicts() {
+        let mut backing = HpetBacking::new();
+        let mut hpet = Hpet::from_backing(&mut backing);
+        for index in 0..3 {
+            let mut timer = hpet.timer_mut(index);
+            timer.configure_interrupt(InterruptConfig::IoApic(20));
+            timer.set_interrupt_enable(index != 2);
+        }
+        assert!(hpet.irq_routing_conflicts().eq([(0, 1, 20)]));
+        hpet.timer_mut(2).set_interrupt_enable(true);
+        assert!(
+            hpet.irq_routing_conflicts()
+                .eq([(0, 1, 20), (0, 2, 20), (1, 2, 20)])
+        );
+        hpet.set_legacy_replacement(true);
+        assert_eq!(hpet.irq_routing_conflicts().count(), 0);
+    }
+
+    #[test]
+    fn clear_interrupts_mask_ignores_missing_timers() {
+        let mut backing = HpetBacking::new();
+        let mut hpet = Hpet::from_backing(&mut backing);
+        // The fake registers are plain memory, so the written value reads back
+        hpet.clear_interrupts_mask(u64::MAX);
+        assert_eq!(hpet.raw_interrupt_status(), 0b111);
+        hpet.clear_interrupts_mask(0b1010);
+        assert_eq!(hpet.raw_interrupt_status(), 0b10);
+    }
+
+    #[test]
+    fn setup_legacy_pit_checks_frequency() {
+        let mut backing = HpetBacking::new();
+        let mut hpet = Hpet::from_backing(&mut backing);
+        assert_eq!(
+            hpet.setup_legacy_pit(0),
+            Err(LegacyPitError::InvalidFrequency(0))
+        );
+        assert_eq!(
+            hpet.setup_legacy_pit(100_000_000),
+            Err(LegacyPitError::InvalidFrequency(100_000_000))
+        );
+        assert!(!hpet.get_enable());
+        assert!(!hpet.get_legacy_replacement_enabled());
+        assert_eq!(hpet.setup_legacy_pit(1000), Ok(()));
+        assert!(hpet.legacy_replacement_active());
+        assert!(hpet.timer(0).is_periodic());
+    }
+
+    #[test]
+    fn periodic_frequency_hz() {
+        let mut backing = HpetBacking::new();
+        let mut hpet = Hpet::from_backing(&mut backing);
+        assert_eq!(hpet.timer(0).periodic_frequency_hz(), None);
+        hpet.setup_legacy_pit(1000).unwrap();
+        assert_eq!(hpet.timer(0).period_ticks(), Some(14318));
+        assert_eq!(hpet.timer(0).periodic_frequency_hz(), Some(1000));
+        hpet.timer_mut(0).set_one_shot();
+        assert_eq!(hpet.timer(0).periodic_frequency_hz(), None);
+    }
+}
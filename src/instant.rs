@@ -0,0 +1,45 @@
+use core::time::Duration;
+
+use crate::*;
+
+/// A captured main counter value, similar to `std::time::Instant`. Get one with [`Hpet::now`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HpetInstant(u64);
+
+impl HpetInstant {
+    /// The main counter value that was captured
+    pub fn ticks(&self) -> u64 {
+        self.0
+    }
+
+    /// The time between `earlier` and this instant. The main counter wrapping between the two instants is handled, but only once.
+    pub fn duration_since(&self, earlier: HpetInstant, hpet: &Hpet) -> Duration {
+        let ticks = self.0.wrapping_sub(earlier.0) & hpet.main_counter_mask();
+        hpet.counter_value_to_duration(ticks)
+    }
+}
+
+/// A main counter value captured with [`Hpet::snapshot`], for arming timers relative to the same point in time with [`HpetTimerMut::arm_relative_to`]
+pub type CounterSnapshot = HpetInstant;
+
+impl Hpet<'_> {
+    pub fn now(&self) -> HpetInstant {
+        HpetInstant(self.main_counter_value())
+    }
+
+    /// Same as [`Hpet::now`]
+    pub fn snapshot(&self) -> CounterSnapshot {
+        self.now()
+    }
+}
+
+impl HpetTimerMut<'_> {
+    /// Puts the timer in one-shot mode, firing `ticks` main counter ticks after `snapshot` was taken.
+    /// You can use the same snapshot to arm multiple timers relative to the same point in time.
+    ///
+    /// **Note**: if the snapshot is too old, the deadline may already be in the past, and the timer will not fire until the main counter wraps around.
+    pub fn arm_relative_to(&mut self, snapshot: &CounterSnapshot, ticks: u64) {
+        self.set_one_shot();
+        self.set_comparator_value(self.next_deadline(snapshot.ticks(), ticks));
+    }
+}
@@ -0,0 +1,44 @@
+use volatile::VolatilePtr;
+
+use crate::*;
+
+/// Clears a timer's interrupt status when dropped, so that the interrupt is acknowledged even if the interrupt handler returns early or panics.
+/// Get one with [`Hpet::ack_on_drop`].
+///
+/// **Note**: this only makes sense for level-triggered timers. Edge-triggered timers don't latch the interrupt status, so there is nothing to clear.
+pub struct InterruptAck<'a> {
+    mmio: Fenced<VolatilePtr<'a, HpetMemory>>,
+    index: u8,
+}
+
+impl InterruptAck<'_> {
+    /// The index of the timer whose interrupt will be cleared
+    pub fn timer_index(&self) -> u8 {
+        self.index
+    }
+}
+
+impl Drop for InterruptAck<'_> {
+    fn drop(&mut self) {
+        // Writing 0 to the other bits has no effect, so we don't read the register first
+        let mut reg = HpetGeneralInterruptStatusRegister(0);
+        reg.set_t_n_int_sts(self.index as usize, 1);
+        self.mmio.interrupt_status().write(reg);
+    }
+}
+
+impl Hpet<'_> {
+    /// Returns a guard that clears the timer's interrupt (just like [`Hpet::clear_timer_interrupt`]) when it is dropped.
+    /// Create it at the start of the interrupt handler.
+    ///
+    /// Panics if the timer doesn't exist.
+    pub fn ack_on_drop(&mut self, timer_index: u8) -> InterruptAck {
+        if timer_index >= self.timers_count() {
+            panic!("Tried to access timer {timer_index}, which is not supported by this HPET");
+        }
+        InterruptAck {
+            mmio: self.regs_mut(),
+            index: timer_index,
+        }
+    }
+}
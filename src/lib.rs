@@ -1,8 +1,47 @@
 //! Get started by obtaining an [`Hpet`] using [`Hpet::new`].
 #![no_std]
 #![feature(debug_closure_helpers)]
+#[cfg(feature = "acpi")]
+mod acpi_support;
+mod convert;
+mod counter_tracker;
+#[cfg(feature = "embedded-hal")]
+mod delay;
+mod dump;
+mod error;
+#[cfg(feature = "defmt")]
+mod format;
 mod hpet;
+mod instant;
+mod interrupt_ack;
+mod long_timeout;
 mod mmio;
+#[cfg(feature = "bytemuck")]
+mod pod;
+mod state;
+#[cfg(any(test, feature = "testing"))]
+mod testing;
+mod ticks;
+mod timer_config;
+mod timestamp_log;
+mod vendor;
 
+#[cfg(feature = "acpi")]
+pub use acpi_support::*;
+pub use convert::*;
+pub use counter_tracker::*;
+#[cfg(feature = "embedded-hal")]
+pub use delay::*;
+pub use error::*;
 pub use hpet::*;
+pub use instant::*;
+pub use interrupt_ack::*;
+pub use long_timeout::*;
 pub use mmio::*;
+pub use state::*;
+#[cfg(any(test, feature = "testing"))]
+pub use testing::*;
+pub use ticks::*;
+pub use timer_config::*;
+pub use timestamp_log::*;
+pub use vendor::*;
@@ -0,0 +1,85 @@
+use core::time::Duration;
+
+use crate::*;
+
+/// A one-shot timeout that can be longer than what the timer's comparator can represent (see [`HpetTimerRef::max_one_shot_ticks`]).
+/// Get one with [`HpetTimerMut::arm_long_one_shot`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LongTimeout {
+    remaining_ticks: u64,
+}
+
+impl LongTimeout {
+    /// The ticks that still need to be waited for after the currently armed deadline
+    pub fn remaining_ticks(&self) -> u64 {
+        self.remaining_ticks
+    }
+
+    /// Call this every time the timer fires. Arms the timer for the next part of the timeout, and returns `Ok(true)`.
+    /// Returns `Ok(false)` without arming the timer if the whole timeout has elapsed.
+    ///
+    /// The next part is counted from the deadline that just fired, not from when this is called, so the interrupt handler's latency doesn't add up over the parts.
+    /// Returns an error if the main counter already passed the next deadline when it was armed, in which case the timeout has elapsed.
+    pub fn rearm(&mut self, timer: &mut HpetTimerMut) -> Result<bool, ArmError> {
+        if self.remaining_ticks == 0 {
+            return Ok(false);
+        }
+        let ticks = self.remaining_ticks.min(timer.max_one_shot_ticks());
+        self.remaining_ticks -= ticks;
+        let previous_deadline = timer.comparator_value();
+        timer.try_arm_one_shot_from(previous_deadline, ticks)?;
+        Ok(true)
+    }
+}
+
+impl HpetTimerMut<'_> {
+    /// Arms a one-shot timeout which can be longer than [`HpetTimerRef::max_one_shot_ticks`], which is useful for 32-bit timers.
+    /// If the timeout is too long, the timer is armed for as long as it can be,
+    /// and you need to call [`LongTimeout::rearm`] every time the timer fires until it returns `Ok(false)`.
+    ///
+    /// Returns [`ArmError::DeadlineInPast`] if the main counter already passed the deadline when it was armed, which always happens for a timeout of 0 ticks.
+    /// The timer will not fire in that case, so handle the timeout right away.
+    pub fn arm_long_one_shot(&mut self, total: Duration) -> Result<LongTimeout, ArmError> {
+        let total_ticks = ticks_for_duration(self.main_counter_tick_period(), total);
+        let ticks = total_ticks.min(self.max_one_shot_ticks());
+        self.try_arm_one_shot_after(ticks)?;
+        Ok(LongTimeout {
+            remaining_ticks: total_ticks - ticks,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rearm_chains_from_previous_deadline() {
+        let mut backing = HpetBacking::new();
+        let mut hpet = Hpet::from_backing(&mut backing);
+        let total = Duration::from_secs(600);
+        let total_ticks = ticks_for_duration(hpet.main_counter_tick_period(), total);
+        let mut timer = hpet.timer_mut(1);
+        timer.set_force_32_bit(true);
+        let mut timeout = timer.arm_long_one_shot(total).unwrap();
+        assert_eq!(timer.comparator_value(), u32::MAX as u64);
+        assert_eq!(timeout.remaining_ticks(), total_ticks - u32::MAX as u64);
+
+        // The interrupt handler runs 100 ticks after the deadline
+        hpet.set_main_counter_value(u32::MAX as u64 + 100);
+        let mut timer = hpet.timer_mut(1);
+        assert_eq!(timeout.rearm(&mut timer), Ok(true));
+        assert_eq!(timer.comparator_value(), 0xFFFF_FFFE);
+        assert_eq!(timeout.remaining_ticks(), total_ticks - 2 * u32::MAX as u64);
+    }
+
+    #[test]
+    fn zero_timeout_is_in_the_past() {
+        let mut backing = HpetBacking::new();
+        let mut hpet = Hpet::from_backing(&mut backing);
+        assert_eq!(
+            hpet.timer_mut(1).arm_long_one_shot(Duration::ZERO),
+            Err(ArmError::DeadlineInPast)
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use core::mem::MaybeUninit;
+use core::mem::{MaybeUninit, offset_of};
 
 use bitfield::bitfield;
 use volatile::{
@@ -7,11 +7,11 @@ use volatile::{
 };
 
 /// Based on:
-/// - https://www.intel.com/content/dam/www/public/us/en/documents/technical-specifications/software-developers-hpet-spec-1-0a.pdf
-/// - https://wiki.osdev.org/HPET#HPET_registers
+/// - <https://www.intel.com/content/dam/www/public/us/en/documents/technical-specifications/software-developers-hpet-spec-1-0a.pdf>
+/// - <https://wiki.osdev.org/HPET#HPET_registers>
 #[repr(C)]
 #[derive(Debug, VolatileFieldAccess)]
-pub(crate) struct HpetMemory {
+pub struct HpetMemory {
     #[access(ReadOnly)]
     pub capabilities_and_id: HpetGeneralCapabilitiesAndIdRegister,
     #[access(NoAccess)]
@@ -31,14 +31,46 @@ pub(crate) struct HpetMemory {
     _reserved_0f8_0ff: [MaybeUninit<u8>; 0x8],
     #[access(ReadWrite)]
     /// There is memory for 32 timers, but there are not always physically 32 timers. Check the number of timers before accessing a timer's memory.
-    pub timers: [HpetTimerMemory; 32],
+    pub timers: [HpetTimerMemory; HPET_MAX_TIMERS],
 }
 
 pub const HPET_MMIO_SIZE: usize = size_of::<HpetMemory>();
 
+/// The maximum tick period allowed by the spec, in femtoseconds (100 ns)
+pub const MAX_TICK_PERIOD_FS: u32 = 0x05F5E100;
+
+/// The maximum number of timers that an HPET can have
+pub const HPET_MAX_TIMERS: usize = 32;
+
+/// The byte offset of the General Capabilities and ID Register from the start of the HPET's memory
+pub const HPET_CAPABILITIES_OFFSET: usize = offset_of!(HpetMemory, capabilities_and_id);
+
+/// The byte offset of the General Configuration Register from the start of the HPET's memory
+pub const HPET_CONFIG_OFFSET: usize = offset_of!(HpetMemory, config);
+
+/// The byte offset of the General Interrupt Status Register from the start of the HPET's memory
+pub const HPET_INTERRUPT_STATUS_OFFSET: usize = offset_of!(HpetMemory, interrupt_status);
+
+/// The byte offset of the main counter value register from the start of the HPET's memory
+pub const HPET_MAIN_COUNTER_OFFSET: usize = offset_of!(HpetMemory, main_counter_value_register);
+
+/// The byte offset of timer 0's registers from the start of the HPET's memory
+pub const HPET_TIMER0_OFFSET: usize = offset_of!(HpetMemory, timers);
+
+/// The number of bytes between each timer's registers
+pub const HPET_TIMER_STRIDE: usize = size_of::<HpetTimerMemory>();
+
+// The offsets from the spec
+const _: () = assert!(HPET_CAPABILITIES_OFFSET == 0x00);
+const _: () = assert!(HPET_CONFIG_OFFSET == 0x10);
+const _: () = assert!(HPET_INTERRUPT_STATUS_OFFSET == 0x20);
+const _: () = assert!(HPET_MAIN_COUNTER_OFFSET == 0xF0);
+const _: () = assert!(HPET_TIMER0_OFFSET == 0x100);
+const _: () = assert!(HPET_TIMER_STRIDE == 0x20);
+
 bitfield! {
     #[repr(transparent)]
-    #[derive(Copy, Clone)]
+    #[derive(Clone, Copy, PartialEq, Eq)]
     pub struct HpetGeneralCapabilitiesAndIdRegister(u64);
     impl Debug;
 
@@ -72,8 +104,9 @@ bitfield! {
 
 bitfield! {
     #[repr(transparent)]
-    #[derive(Copy, Clone)]
-    pub(crate) struct HpetGeneralConfigurationRegister(u64);
+    #[derive(Clone, Copy, PartialEq, Eq)]
+    /// General Configuration Register
+    pub struct HpetGeneralConfigurationRegister(u64);
     impl Debug;
 
     /// From the docs:
@@ -100,7 +133,7 @@ bitfield! {
 bitfield! {
     /// General Interrupt Status Register
     #[repr(transparent)]
-    #[derive(Clone, Copy)]
+    #[derive(Clone, Copy, PartialEq, Eq)]
     pub struct HpetGeneralInterruptStatusRegister(u64);
     impl Debug;
 
@@ -114,7 +147,7 @@ bitfield! {
 
 #[repr(C)]
 #[derive(Debug, VolatileFieldAccess)]
-pub(crate) struct HpetTimerMemory {
+pub struct HpetTimerMemory {
     pub configuration_and_capability_register: TimerNConfigurationAndCapabilityRegister,
     pub comparator_register: u64,
     pub fsb_interrupt_route_register: TimerNFsbInterruptRouteRegister,
@@ -124,7 +157,7 @@ pub(crate) struct HpetTimerMemory {
 bitfield! {
     /// Timer N Configuration and Capability Register
     #[repr(transparent)]
-    #[derive(Clone, Copy)]
+    #[derive(Clone, Copy, PartialEq, Eq)]
     pub struct TimerNConfigurationAndCapabilityRegister(u64);
     impl Debug;
 
@@ -174,7 +207,8 @@ bitfield! {
 
 bitfield! {
     /// Timer N FSB Interrupt Route Register
-    #[derive(Clone, Copy)]
+    #[repr(transparent)]
+    #[derive(Clone, Copy, PartialEq, Eq)]
     pub struct TimerNFsbInterruptRouteRegister(u64);
     impl Debug;
 
@@ -187,3 +221,29 @@ bitfield! {
     /// > Software sets this 32-bit field to indicate the value that is written during the FSB interrupt message.
     pub fsb_int_val, set_fsb_int_val: 31, 0;
 }
+
+impl TimerNFsbInterruptRouteRegister {
+    /// - `addr` is the address that the FSB interrupt message will be written to
+    /// - `value` is the data that will be written in the FSB interrupt message
+    pub fn new(addr: u32, value: u32) -> Self {
+        let mut reg = Self(0);
+        reg.set_fsb_int_addr(addr);
+        reg.set_fsb_int_val(value);
+        reg
+    }
+}
+
+/// The FSB interrupt message that a timer will send, for use with [`crate::InterruptConfig::Fsb`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FsbRoute {
+    /// The address that the message will be written to
+    pub address: u32,
+    /// The data that will be written
+    pub data: u32,
+}
+
+impl From<FsbRoute> for TimerNFsbInterruptRouteRegister {
+    fn from(route: FsbRoute) -> Self {
+        Self::new(route.address, route.data)
+    }
+}
@@ -0,0 +1,63 @@
+use bytemuck::{Pod, Zeroable};
+
+use crate::*;
+
+macro_rules! impl_pod {
+    ($($t:ty),*) => {
+        $(
+            unsafe impl Zeroable for $t {}
+            unsafe impl Pod for $t {}
+        )*
+    };
+}
+
+// Safety: the registers are `#[repr(transparent)]` over `u64`, and the state structs are `#[repr(C)]` with no padding and only `Pod` fields.
+impl_pod!(
+    HpetGeneralCapabilitiesAndIdRegister,
+    HpetGeneralConfigurationRegister,
+    HpetGeneralInterruptStatusRegister,
+    TimerNConfigurationAndCapabilityRegister,
+    TimerNFsbInterruptRouteRegister,
+    HpetTimerState,
+    HpetState
+);
+
+const _: () = assert!(size_of::<HpetTimerState>() == 4 * size_of::<u64>());
+const _: () = assert!(
+    size_of::<HpetState>() == 3 * size_of::<u64>() + HPET_MAX_TIMERS * size_of::<HpetTimerState>()
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip<T: Pod + PartialEq + core::fmt::Debug>(value: T) {
+        assert_eq!(
+            *bytemuck::from_bytes::<T>(bytemuck::bytes_of(&value)),
+            value
+        );
+    }
+
+    #[test]
+    fn registers() {
+        round_trip(HpetGeneralCapabilitiesAndIdRegister(0x0429_B17F_8086_A201));
+        round_trip(HpetGeneralConfigurationRegister(0b11));
+        round_trip(HpetGeneralInterruptStatusRegister(0b101));
+        round_trip(TimerNConfigurationAndCapabilityRegister(
+            0x00F0_0000_0000_0034,
+        ));
+        round_trip(TimerNFsbInterruptRouteRegister(0xFEE0_0000_0000_0041));
+    }
+
+    #[test]
+    fn state() {
+        let mut backing = HpetBacking::new();
+        let mut hpet = Hpet::from_backing(&mut backing);
+        hpet.timer_mut(1).set_comparator_value(1234);
+        let state = hpet.save_state();
+        let bytes = bytemuck::bytes_of(&state);
+        assert_eq!(bytes.len(), size_of::<HpetState>());
+        assert_eq!(*bytemuck::from_bytes::<HpetState>(bytes), state);
+        round_trip(state.timers()[1]);
+    }
+}
@@ -0,0 +1,173 @@
+use crate::*;
+
+/// A saved copy of the HPET's registers, for restoring after suspend / resume. Get one with [`Hpet::save_state`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub struct HpetState {
+    config: HpetGeneralConfigurationRegister,
+    main_counter_value: u64,
+    timers: [HpetTimerState; HPET_MAX_TIMERS],
+    timers_count: u8,
+    /// So that there are no uninitialized padding bytes
+    _padding: [u8; 7],
+}
+
+impl HpetState {
+    pub fn enabled(&self) -> bool {
+        self.config.get_enable_cnf()
+    }
+
+    pub fn legacy_replacement_enabled(&self) -> bool {
+        self.config.get_legacy_replacement_cnf()
+    }
+
+    pub fn main_counter_value(&self) -> u64 {
+        self.main_counter_value
+    }
+
+    /// The saved state of each timer that exists
+    pub fn timers(&self) -> &[HpetTimerState] {
+        &self.timers[..(self.timers_count as usize).min(self.timers.len())]
+    }
+}
+
+/// A saved copy of a single timer's registers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub struct HpetTimerState {
+    pub config: TimerNConfigurationAndCapabilityRegister,
+    /// For periodic timers, this is the next deadline, not the period
+    pub comparator_value: u64,
+    pub fsb_route: TimerNFsbInterruptRouteRegister,
+    /// The period of a periodic timer (see [`HpetTimer::period_ticks`]), or 0 if the timer isn't periodic or the period isn't known
+    pub period_ticks: u64,
+}
+
+impl Hpet<'_> {
+    pub fn save_state(&self) -> HpetState {
+        let timers_count = self.timers_count();
+        let mut timers = [HpetTimerState {
+            config: TimerNConfigurationAndCapabilityRegister(0),
+            comparator_value: 0,
+            fsb_route: TimerNFsbInterruptRouteRegister(0),
+            period_ticks: 0,
+        }; HPET_MAX_TIMERS];
+        for (index, timer_state) in (0..timers_count).zip(timers.iter_mut()) {
+            let config = self
+                .timer_regs(index)
+                .configuration_and_capability_register()
+                .read();
+            *timer_state = HpetTimerState {
+                config,
+                comparator_value: self.timer_regs(index).comparator_register().read(),
+                fsb_route: self.timer_regs(index).fsb_interrupt_route_register().read(),
+                period_ticks: if config.get_type_cnf() {
+                    self.periods_ticks[index as usize]
+                } else {
+                    0
+                },
+            };
+        }
+        HpetState {
+            config: self.regs().config().read(),
+            main_counter_value: self.main_counter_value(),
+            timers,
+            timers_count,
+            _padding: [0; 7],
+        }
+    }
+
+    /// Restores the registers saved with [`Hpet::save_state`].
+    /// The main counter is halted while restoring, and is re-enabled at the end if it was enabled when the state was saved.
+    ///
+    /// Periodic timers are restored with their saved next deadline and period.
+    /// The HPET does not let you read back the period, so if it isn't known (see [`HpetTimer::period_ticks`]), the timer's comparator is not restored and its interrupt is left disabled.
+    /// Re-arm such timers with [`HpetTimerMut::set_periodic`].
+    pub fn restore_state(&mut self, state: &HpetState) {
+        // The main counter must be halted before it can be written to
+        self.set_enable(false);
+        self.regs_mut().config().update(|mut reg| {
+            reg.set_legacy_replacement_cnf(state.config.get_legacy_replacement_cnf());
+            reg
+        });
+        self.set_main_counter_value(state.main_counter_value);
+        let timers_count = state.timers_count.min(self.timers_count());
+        for (index, timer_state) in (0..timers_count).zip(state.timers.iter()) {
+            let periodic = timer_state.config.get_type_cnf();
+            let period_known = timer_state.period_ticks != 0;
+            // Keep the interrupt disabled until the rest of the timer is restored
+            let mut config = timer_state.config;
+            config.set_int_enb_cnf(false);
+            config.set_val_set_cnf(periodic && period_known);
+            self.timer_regs_mut(index)
+                .configuration_and_capability_register()
+                .write(config);
+            if !periodic {
+                self.timer_regs_mut(index)
+                    .comparator_register()
+                    .write(timer_state.comparator_value);
+            } else if period_known {
+                // After setting `Tn_VAL_SET_CNF`, the first write sets the time of the next interrupt,
+                // and the second write sets the accumulator (the period)
+                self.timer_regs_mut(index)
+                    .comparator_register()
+                    .write(timer_state.comparator_value);
+                self.timer_regs_mut(index)
+                    .comparator_register()
+                    .write(timer_state.period_ticks);
+            }
+            self.periods_ticks[index as usize] = timer_state.period_ticks;
+            self.timer_regs_mut(index)
+                .fsb_interrupt_route_register()
+                .write(timer_state.fsb_route);
+            config.set_val_set_cnf(false);
+            config.set_int_enb_cnf(
+                timer_state.config.get_int_enb_cnf() && (!periodic || period_known),
+            );
+            self.timer_regs_mut(index)
+                .configuration_and_capability_register()
+                .write(config);
+        }
+        self.set_enable(state.config.get_enable_cnf());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restores_period() {
+        let mut backing = HpetBacking::new();
+        let mut hpet = Hpet::from_backing(&mut backing);
+        let mut timer = hpet.timer_mut(0);
+        timer.set_periodic(1000);
+        timer.set_interrupt_enable(true);
+        let state = hpet.save_state();
+        assert_eq!(state.timers()[0].period_ticks, 1000);
+
+        hpet.timer_mut(0).set_one_shot();
+        hpet.restore_state(&state);
+        let timer = hpet.timer(0);
+        assert!(timer.is_periodic());
+        assert!(timer.interrupt_enabled());
+        assert_eq!(timer.period_ticks(), Some(1000));
+    }
+
+    #[test]
+    fn unknown_period_is_left_disabled() {
+        let mut backing = HpetBacking::new();
+        let mut hpet = Hpet::from_backing(&mut backing);
+        let mut timer = hpet.timer_mut(0);
+        timer.set_periodic(1000);
+        timer.set_interrupt_enable(true);
+        let mut state = hpet.save_state();
+        state.timers[0].period_ticks = 0;
+
+        hpet.restore_state(&state);
+        let timer = hpet.timer(0);
+        assert!(timer.is_periodic());
+        assert!(!timer.interrupt_enabled());
+        assert_eq!(timer.period_ticks(), None);
+    }
+}
@@ -0,0 +1,112 @@
+use core::{mem::offset_of, ptr::NonNull};
+
+use volatile::VolatileRef;
+
+use crate::*;
+
+/// The tick period of the fake HPET, which is the typical 14.31818 MHz
+const TICK_PERIOD_FS: u64 = 69_841_279;
+/// The number of timers in the fake HPET
+const TIMERS_COUNT: u64 = 3;
+
+/// Memory for [`Hpet::from_backing`], which is aligned like the HPET's registers
+#[repr(C, align(8))]
+pub struct HpetBacking(pub [u8; HPET_MMIO_SIZE]);
+
+impl HpetBacking {
+    pub const fn new() -> Self {
+        Self([0; HPET_MMIO_SIZE])
+    }
+}
+
+impl Default for HpetBacking {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+const _: () = assert!(align_of::<HpetBacking>() == align_of::<HpetMemory>());
+
+impl<'a> Hpet<'a> {
+    /// Creates an [`Hpet`] backed by regular memory instead of a real HPET, so that you can test code that uses the HPET without real hardware.
+    ///
+    /// The buffer is zeroed, and then its capability registers are filled in to look like a typical 14.31818 MHz HPET with 3 64-bit timers,
+    /// where timer 0 supports periodic mode, and every timer can be routed to I/O APIC IRQs 20-23.
+    /// Nothing happens on its own (the main counter doesn't advance), but you can read back the registers that were written.
+    pub fn from_backing(backing: &'a mut HpetBacking) -> Self {
+        let buf = &mut backing.0;
+        buf.fill(0);
+        // COUNTER_CLK_PERIOD, VENDOR_ID, LEG_RT_CAP, COUNT_SIZE_CAP, NUM_TIM_CAP, REV_ID
+        let capabilities = (TICK_PERIOD_FS << 32)
+            | (0x8086 << 16)
+            | (1 << 15)
+            | (1 << 13)
+            | ((TIMERS_COUNT - 1) << 8)
+            | 1;
+        write_u64(buf, HPET_CAPABILITIES_OFFSET, capabilities);
+        for index in 0..TIMERS_COUNT as usize {
+            // Tn_INT_ROUTE_CAP, Tn_SIZE_CAP
+            let mut config = (0x00F0_0000 << 32) | (1 << 5);
+            if index == 0 {
+                // Tn_PER_INT_CAP
+                config |= 1 << 4;
+            }
+            write_u64(
+                buf,
+                HPET_TIMER0_OFFSET
+                    + index * HPET_TIMER_STRIDE
+                    + offset_of!(HpetTimerMemory, configuration_and_capability_register),
+                config,
+            );
+        }
+        Self::from_volatile_ref(unsafe { VolatileRef::new(NonNull::from(buf).cast()) })
+    }
+}
+
+fn write_u64(buf: &mut [u8], offset: usize, value: u64) {
+    buf[offset..offset + size_of::<u64>()].copy_from_slice(&value.to_le_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn capabilities() {
+        let mut backing = HpetBacking::new();
+        let hpet = Hpet::from_backing(&mut backing);
+        assert_eq!(
+            hpet.capabilities(),
+            HpetCapabilities {
+                vendor_id: 0x8086,
+                num_timers: 3,
+                tick_period_fs: 69_841_279,
+                legacy_replacement_capable: true,
+                supports_64_bit: true,
+                revision_id: 1,
+            }
+        );
+        assert!(hpet.is_standard_frequency());
+        assert!(hpet.timer(0).supports_periodic_mode());
+        assert!(!hpet.timer(1).supports_periodic_mode());
+        assert_eq!(hpet.timer(2).effective_timer_width(), 64);
+        assert!(hpet.timer(1).supported_io_apic_irqs().eq([20, 21, 22, 23]));
+    }
+
+    #[test]
+    fn registers_read_back() {
+        let mut backing = HpetBacking::new();
+        let mut hpet = Hpet::from_backing(&mut backing);
+        hpet.set_main_counter_value(1234);
+        assert_eq!(hpet.main_counter_value(), 1234);
+        let mut timer = hpet.timer_mut(1);
+        timer.configure_interrupt(InterruptConfig::IoApic(21));
+        timer.set_comparator_value(5678);
+        assert_eq!(timer.io_apic_irq(), Some(21));
+        assert_eq!(timer.comparator_value(), 5678);
+        assert_eq!(
+            timer.try_configure_interrupt(InterruptConfig::IoApic(2)),
+            Err(InterruptConfigError::UnsupportedIrq(2))
+        );
+    }
+}
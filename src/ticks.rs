@@ -0,0 +1,85 @@
+use core::{
+    ops::{Add, AddAssign, Sub, SubAssign},
+    time::Duration,
+};
+
+use crate::*;
+
+/// A number of main counter ticks, so that ticks don't get mixed up with other units.
+/// Arithmetic wraps at `u64::MAX` (in both debug and release builds), like the 64-bit main counter does.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Ticks(pub u64);
+
+impl Ticks {
+    /// See [`duration_for_ticks`] for precision notes
+    pub fn to_duration(self, hpet: &Hpet) -> Duration {
+        hpet.counter_value_to_duration(self.0)
+    }
+
+    /// Rounds to the nearest tick, saturating at `u64::MAX`
+    pub fn from_duration(duration: Duration, hpet: &Hpet) -> Self {
+        Self(ticks_for_duration(
+            hpet.main_counter_tick_period(),
+            duration,
+        ))
+    }
+}
+
+impl From<u64> for Ticks {
+    fn from(ticks: u64) -> Self {
+        Self(ticks)
+    }
+}
+
+impl From<Ticks> for u64 {
+    fn from(ticks: Ticks) -> Self {
+        ticks.0
+    }
+}
+
+impl Add for Ticks {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0.wrapping_add(rhs.0))
+    }
+}
+
+impl AddAssign for Ticks {
+    fn add_assign(&mut self, rhs: Self) {
+        self.0 = self.0.wrapping_add(rhs.0);
+    }
+}
+
+impl Sub for Ticks {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self(self.0.wrapping_sub(rhs.0))
+    }
+}
+
+impl SubAssign for Ticks {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.0 = self.0.wrapping_sub(rhs.0);
+    }
+}
+
+impl Hpet<'_> {
+    /// Same as [`Hpet::main_counter_value`]
+    pub fn main_counter_ticks(&self) -> Ticks {
+        Ticks(self.main_counter_value())
+    }
+}
+
+impl HpetTimerMut<'_> {
+    /// Same as [`HpetTimerMut::set_comparator_value`]
+    pub fn set_comparator_ticks(&mut self, comparator_value: Ticks) {
+        self.set_comparator_value(comparator_value.0);
+    }
+
+    /// Same as [`HpetTimerMut::arm_one_shot_after`]
+    pub fn arm_one_shot_after_ticks(&mut self, ticks: Ticks) {
+        self.arm_one_shot_after(ticks.0);
+    }
+}
@@ -0,0 +1,139 @@
+use crate::*;
+
+/// Everything needed to set up a timer, for use with [`HpetTimerMut::apply`]
+#[derive(Debug, Clone, Copy)]
+pub struct TimerConfig {
+    pub interrupt: InterruptConfig,
+    pub trigger: InterruptTrigger,
+    pub mode: TimerMode,
+    pub interrupt_enable: bool,
+}
+
+impl TimerConfig {
+    /// Defaults to edge-triggered, with the interrupt enabled
+    pub fn new(interrupt: InterruptConfig, mode: TimerMode) -> Self {
+        Self {
+            interrupt,
+            trigger: InterruptTrigger::Edge,
+            mode,
+            interrupt_enable: true,
+        }
+    }
+
+    pub fn with_trigger(mut self, trigger: InterruptTrigger) -> Self {
+        self.trigger = trigger;
+        self
+    }
+
+    pub fn with_interrupt_enable(mut self, interrupt_enable: bool) -> Self {
+        self.interrupt_enable = interrupt_enable;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimerMode {
+    /// Fire once, when the main counter reaches `comparator_value`
+    OneShot { comparator_value: u64 },
+    /// Fire every `period_ticks`, starting `period_ticks` from when the config is applied
+    Periodic { period_ticks: u64 },
+}
+
+impl HpetTimerMut<'_> {
+    /// Configures the interrupt routing, trigger, and mode, and then enables or disables the interrupt.
+    /// The interrupt is disabled while configuring the timer.
+    /// If an error is returned, the timer is left unchanged.
+    pub fn apply(&mut self, config: &TimerConfig) -> Result<(), TimerConfigError> {
+        self.check_interrupt_config(&config.interrupt)?;
+        self.check_mode(&config.mode)?;
+        self.set_interrupt_enable(false);
+        self.try_configure_interrupt(config.interrupt)?;
+        self.apply_without_route(config);
+        Ok(())
+    }
+
+    /// Checks that `mode` can be used with this timer
+    pub(crate) fn check_mode(&self, mode: &TimerMode) -> Result<(), TimerConfigError> {
+        if matches!(mode, TimerMode::Periodic { .. }) && !self.supports_periodic_mode() {
+            return Err(TimerConfigError::PeriodicUnsupported);
+        }
+        let value = match *mode {
+            TimerMode::OneShot { comparator_value } => comparator_value,
+            TimerMode::Periodic { period_ticks } => period_ticks,
+        };
+        if value & !self.max_one_shot_ticks() != 0 {
+            return Err(TimerConfigError::ComparatorTooWide(value));
+        }
+        Ok(())
+    }
+
+    /// Same as [`HpetTimerMut::apply`], but leaves the interrupt routing unchanged.
+    /// The mode must already be checked with [`HpetTimerMut::check_mode`], and the interrupt must already be disabled.
+    pub(crate) fn apply_without_route(&mut self, config: &TimerConfig) {
+        self.set_interrupt_trigger(config.trigger);
+        match config.mode {
+            TimerMode::OneShot { comparator_value } => {
+                self.set_one_shot();
+                self.set_comparator_value(comparator_value);
+            }
+            TimerMode::Periodic { period_ticks } => self.set_periodic(period_ticks),
+        }
+        self.set_interrupt_enable(config.interrupt_enable);
+    }
+    /// Reads back the timer's registers and checks if they match `expected`, for example to check that [`HpetTimerMut::apply`] took effect.
+    ///
+    /// **Note**: the period of a periodic timer cannot be read back, so only the mode is checked for [`TimerMode::Periodic`].
+    pub fn verify_config(&self, expected: &TimerConfig) -> bool {
+        let route_matches = match expected.interrupt {
+            InterruptConfig::IoApic(irq) => self.io_apic_irq() == Some(irq),
+            InterruptConfig::Fsb(route) => {
+                self.fsb_route() == Some((route.fsb_int_addr(), route.fsb_int_val()))
+            }
+        };
+        let mode_matches = match expected.mode {
+            TimerMode::OneShot { comparator_value } => {
+                !self.is_periodic()
+                    && self.comparator_value() & self.max_one_shot_ticks()
+                        == comparator_value & self.max_one_shot_ticks()
+            }
+            TimerMode::Periodic { .. } => self.is_periodic(),
+        };
+        route_matches
+            && mode_matches
+            && self.interrupt_trigger() == expected.trigger
+            && self.interrupt_enabled() == expected.interrupt_enable
+    }
+}
+
+impl Hpet<'_> {
+    /// Configures timers 0 and 1 while LegacyReplacement Route is enabled.
+    /// The spec fixes their routing (see [`Hpet::set_legacy_replacement`]), and says that the timers' routing bits have no impact, so the interrupt routes in the configs (I/O APIC or FSB) are ignored, and the timers' routing is left unchanged.
+    /// If an error is returned, both timers are left unchanged.
+    pub fn configure_legacy_timers(
+        &mut self,
+        timer0: &TimerConfig,
+        timer1: &TimerConfig,
+    ) -> Result<(), LegacyConfigError> {
+        if !self.legacy_replacement_capable() {
+            return Err(LegacyConfigError::Unsupported);
+        }
+        if !self.get_legacy_replacement_enabled() {
+            return Err(LegacyConfigError::NotEnabled);
+        }
+        let configs = [timer0, timer1];
+        for (index, config) in (0..).zip(configs) {
+            let timer = self
+                .try_timer_mut(index)
+                .ok_or(LegacyConfigError::MissingTimer(index))?;
+            timer
+                .check_mode(&config.mode)
+                .map_err(|error| LegacyConfigError::Timer(index, error))?;
+        }
+        for (index, config) in (0..).zip(configs) {
+            let mut timer = self.timer_mut(index);
+            timer.set_interrupt_enable(false);
+            timer.apply_without_route(config);
+        }
+        Ok(())
+    }
+}
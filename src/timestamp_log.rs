@@ -0,0 +1,72 @@
+use core::time::Duration;
+
+use crate::*;
+
+/// A fixed-size ring buffer of main counter values, for example to measure interrupt latency.
+/// Once `N` timestamps are recorded, the oldest timestamp is overwritten.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimestampLog<const N: usize> {
+    timestamps: [u64; N],
+    /// The index that the next timestamp will be written to
+    next: usize,
+    len: usize,
+}
+
+impl<const N: usize> Default for TimestampLog<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> TimestampLog<N> {
+    pub const fn new() -> Self {
+        Self {
+            timestamps: [0; N],
+            next: 0,
+            len: 0,
+        }
+    }
+
+    /// Records the current main counter value (see [`Hpet::timestamp`])
+    pub fn record(&mut self, hpet: &Hpet) {
+        if N == 0 {
+            return;
+        }
+        self.timestamps[self.next] = hpet.timestamp();
+        self.next = (self.next + 1) % N;
+        self.len = (self.len + 1).min(N);
+    }
+
+    /// The number of timestamps stored, which is at most `N`
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.next = 0;
+        self.len = 0;
+    }
+
+    /// The stored main counter values, from oldest to newest
+    pub fn ticks(&self) -> impl Iterator<Item = u64> {
+        let start = (self.next + N - self.len) % N.max(1);
+        (0..self.len).map(move |i| self.timestamps[(start + i) % N])
+    }
+
+    /// The stored timestamps converted with [`Hpet::counter_value_to_duration`], from oldest to newest
+    pub fn durations<'a>(&'a self, hpet: &'a Hpet) -> impl Iterator<Item = Duration> {
+        self.ticks()
+            .map(|ticks| hpet.counter_value_to_duration(ticks))
+    }
+}
+
+impl Hpet<'_> {
+    /// Same as [`Hpet::main_counter_value`], for recording when something happened
+    pub fn timestamp(&self) -> u64 {
+        self.main_counter_value()
+    }
+}
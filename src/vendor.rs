@@ -0,0 +1,37 @@
+use crate::*;
+
+/// Common PCI vendor IDs that HPETs report
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HpetVendor {
+    /// `8086h`
+    Intel,
+    /// `1022h`
+    Amd,
+    /// `1002h`
+    Ati,
+    /// `10DEh`
+    Nvidia,
+    /// `1106h`
+    Via,
+    Unknown(u16),
+}
+
+impl From<u16> for HpetVendor {
+    fn from(vendor_id: u16) -> Self {
+        match vendor_id {
+            0x8086 => Self::Intel,
+            0x1022 => Self::Amd,
+            0x1002 => Self::Ati,
+            0x10DE => Self::Nvidia,
+            0x1106 => Self::Via,
+            vendor_id => Self::Unknown(vendor_id),
+        }
+    }
+}
+
+impl Hpet<'_> {
+    /// See [`Hpet::vendor_id`] for the raw vendor ID
+    pub fn vendor(&self) -> HpetVendor {
+        self.vendor_id().into()
+    }
+}